@@ -1,5 +1,6 @@
 extern crate exonum;
 extern crate football_voting;
+extern crate serde_json;
 
 
 use exonum::blockchain::{GenesisConfig, ValidatorKeys};
@@ -7,10 +8,48 @@ use exonum::node::{Node, NodeApiConfig, NodeConfig};
 use exonum::storage::MemoryDB;
 
 
+use football_voting::config::{ElectionConfig, TeamConfig, VotesConfig};
+use football_voting::constants::SERVICE_NAME;
+use football_voting::election::BallotMode;
 use football_voting::service::VotesService;
 
 
-fn node_config() -> NodeConfig {
+fn votes_config() -> VotesConfig {
+    // The config of a new network may be exported from another one by `/v1/export`
+    if let Some(path) = std::env::args().nth(1) {
+        let file = std::fs::File::open(path).expect("Can't open the service config");
+        return serde_json::from_reader(file).expect("Invalid service config");
+    }
+
+    let (admin_public_key, admin_secret_key) = exonum::crypto::gen_keypair();
+    println!("Administrator public key: {}", admin_public_key.to_hex());
+    println!("Administrator secret key: {}", admin_secret_key.to_hex());
+
+    let teams = ["Russia", "Brazil", "Germany"]
+        .iter()
+        .map(|name| TeamConfig {
+            pub_key: exonum::crypto::gen_keypair().0,
+            name: name.to_string(),
+        })
+        .collect();
+
+    VotesConfig {
+        admins: vec![admin_public_key],
+        teams,
+        election: Some(ElectionConfig {
+            title: "World Cup winner".to_owned(),
+            start_height: 1,
+            end_height: u64::max_value(),
+            max_votes_per_fan: 1,
+            ballot_mode: BallotMode::Single,
+            reveal_height: 0,
+        }),
+        ..Default::default()
+    }
+}
+
+
+fn node_config(votes_config: &VotesConfig) -> NodeConfig {
     let (consensus_public_key, consensus_secret_key) = exonum::crypto::gen_keypair();
     let (service_public_key, service_secret_key) = exonum::crypto::gen_keypair();
 
@@ -28,6 +67,9 @@ fn node_config() -> NodeConfig {
 
     let peer_address = "0.0.0.0:2000".parse().unwrap();
 
+    let mut services_configs = std::collections::BTreeMap::new();
+    services_configs.insert(SERVICE_NAME.to_owned(), serde_json::to_value(votes_config).unwrap());
+
     NodeConfig {
         listen_address: peer_address,
         peers: vec![],
@@ -41,18 +83,22 @@ fn node_config() -> NodeConfig {
         whitelist: Default::default(),
         api: api_cfg,
         mempool: Default::default(),
-        services_configs: Default::default(),
+        services_configs,
         database: Default::default(),
     }
 }
 
 fn main() {
     exonum::helpers::init_logger().unwrap();
+    let node_config = node_config(&votes_config());
+    let votes_config: VotesConfig = serde_json::from_value(
+        node_config.services_configs[SERVICE_NAME].clone()
+    ).unwrap();
     println!("Creating in-memory database...");
     let node = Node::new(
         MemoryDB::new(),
-        vec![Box::new(VotesService)],
-        node_config(),
+        vec![Box::new(VotesService::new(votes_config))],
+        node_config,
     );
     println!("Starting a single node...");
     println!("Blockchain is ready for transactions!");
@@ -4,17 +4,25 @@ use std::iter::{IntoIterator};
 
 use bodyparser;
 use exonum::{api::{Api, ApiError},
-             blockchain::{Blockchain, Transaction},
+             blockchain::{Blockchain, BlockProof, Schema, Transaction, TransactionErrorType},
+             helpers::Height,
              crypto::{Hash, PublicKey},
              encoding::serialize::FromHex,
              node::{ApiSender, TransactionSend},
-             explorer::{BlockchainExplorer, TransactionInfo::{Committed}}};
+             explorer::{BlockchainExplorer, TransactionInfo},
+             storage::{ListProof, MapProof, ProofMapIndex, Snapshot, StorageValue}};
 use iron::{headers::ContentType, modifiers::Header, prelude::*, status::Status};
 use router::Router;
 use serde_json;
 
 
+use config::{BallotExport, ElectionExport, FanConfig, TeamConfig, VotesConfig};
+use constants::{SERVICE_ID, FAN_WALLETS_TABLE, TEAM_WALLETS_TABLE, TALLIES_TABLE,
+                VOTES_LOG_TABLE, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE, MAX_BATCH_SIZE};
+use election::*;
+use errors::Error;
 use schema::*;
+use tally::{self, TallyMethod};
 use wallet::*;
 use transactions::*;
 
@@ -42,7 +50,347 @@ pub struct TransactionResponse {
 }
 
 
+/// Result of one transaction of a batch. `tx_hash` is `null` if the item
+/// couldn't be parsed, `error` is `null` if the transaction was accepted.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchItemResponse {
+    pub tx_hash: Option<Hash>,
+    pub error: Option<String>,
+}
+
+
+/// Ballot as returned by the API. `vote_hash` is `null` if the fan
+/// has never voted in the election.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BallotInfo {
+    pub election_id: u64,
+    pub fan: PublicKey,
+    pub choices: Vec<TeamVotes>,
+    pub weight: u64,
+    pub vote_hash: Option<Hash>,
+    pub vote_height: u64,
+    pub history_len: u64,
+    pub history_hash: Hash,
+}
+
+
+impl From<Ballot> for BallotInfo {
+    fn from(ballot: Ballot) -> Self {
+        BallotInfo {
+            election_id: ballot.election_id(),
+            fan: *ballot.fan(),
+            choices: ballot.choices(),
+            weight: ballot.weight(),
+            vote_hash: if ballot.history_len() > 0 { Some(*ballot.vote_hash()) } else { None },
+            vote_height: ballot.vote_height(),
+            history_len: ballot.history_len(),
+            history_hash: *ballot.history_hash(),
+        }
+    }
+}
+
+
+/// Fan wallet together with proofs linking it to the latest committed block.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FanWalletProof {
+    pub block_proof: BlockProof,
+    pub to_table: MapProof<Hash, Hash>,
+    pub to_wallet: MapProof<PublicKey, FanWallet>,
+}
+
+
+/// Team wallet together with proofs linking it to the latest committed block.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TeamWalletProof {
+    pub block_proof: BlockProof,
+    pub to_table: MapProof<Hash, Hash>,
+    pub to_wallet: MapProof<PublicKey, TeamWallet>,
+}
+
+
+/// Tally of a candidate together with proofs linking it to the latest committed block.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TallyProof {
+    pub block_proof: BlockProof,
+    pub to_table: MapProof<Hash, Hash>,
+    pub to_tally: MapProof<Hash, Tally>,
+}
+
+
+/// Page of the voters of a candidate. `to_voters` proves the page against
+/// `voters_hash` of the proven tally and is absent if the page is empty.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VotersProof {
+    pub tally_proof: TallyProof,
+    pub to_voters: Option<ListProof<Voter>>,
+}
+
+
+/// Range of the votes log together with proofs linking it to the latest committed block.
+/// `to_votes` is absent if the range is empty.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VotesLogProof {
+    pub block_proof: BlockProof,
+    pub to_table: MapProof<Hash, Hash>,
+    pub len: u64,
+    pub to_votes: Option<ListProof<Hash>>,
+}
+
+
+/// Page of wallets ordered by public key. `next` is the key to pass as `from`
+/// to get the next page, or `null` if this page is the last one.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WalletsPage<W> {
+    pub wallets: Vec<W>,
+    pub next: Option<PublicKey>,
+}
+
+
+/// Collects at most `limit` wallets matching the filter, starting from the key `from`.
+fn wallets_page<W, F>(index: &ProofMapIndex<&Snapshot, PublicKey, W>,
+                      from: Option<PublicKey>,
+                      limit: u64,
+                      filter: F) -> WalletsPage<W>
+    where W: StorageValue, F: Fn(&W) -> bool
+{
+    let iter = match from {
+        Some(ref from) => index.iter_from(from),
+        None => index.iter(),
+    };
+    let mut entries: Vec<(PublicKey, W)> = iter.filter(|&(_, ref wallet)| filter(wallet))
+        .take((limit as usize).saturating_add(1))
+        .collect();
+    let next = if entries.len() as u64 > limit {
+        entries.pop().map(|(key, _)| key)
+    } else {
+        None
+    };
+    let wallets = entries.into_iter().map(|(_, wallet)| wallet).collect();
+    WalletsPage { wallets, next }
+}
+
+
+/// Service state in the form of the genesis config of a new network, see
+/// `/v1/export`. Ballot histories, voter lists and the votes log are not exported.
+fn export_config<T: AsRef<Snapshot>>(schema: &VotesSchema<T>) -> VotesConfig {
+    let export_ballot = |election_id: u64, fan: &PublicKey| BallotExport {
+        fan: *fan,
+        choices: Vec::new(),
+        ranking: Vec::new(),
+        weight: 1,
+        delegators: schema.ballot_delegators(election_id, fan).iter().collect(),
+        commitment: schema.commitment(election_id, fan).map(|commitment| *commitment.commitment()),
+    };
+
+    let mut elections: Vec<ElectionExport> = schema.elections()
+        .iter()
+        .map(|election| {
+            let ballots = if election.mode() == BallotMode::Ranked {
+                schema.ranked_fans(election.id())
+                    .iter()
+                    .filter_map(|fan| schema.ranking(election.id(), &fan))
+                    .map(|ranking| BallotExport {
+                        ranking: ranking.teams(),
+                        weight: ranking.weight(),
+                        ..export_ballot(election.id(), ranking.fan())
+                    })
+                    .collect()
+            } else {
+                // Votes of secret elections which are committed but not revealed yet
+                schema.unrevealed_fans(election.id())
+                    .iter()
+                    .map(|fan| export_ballot(election.id(), fan))
+                    .collect()
+            };
+            ElectionExport {
+                title: election.title().to_owned(),
+                candidates: election.candidates(),
+                start_height: election.start_height(),
+                end_height: election.end_height(),
+                max_votes: election.max_votes(),
+                ballot_mode: election.mode(),
+                reveal_height: election.reveal_height(),
+                ballots,
+            }
+        })
+        .collect();
+    for ballot in schema.ballots().values().filter(|ballot| ballot.voted()) {
+        let choices = ballot.choices()
+            .iter()
+            .map(|choice| (*choice.team(), choice.votes()))
+            .collect();
+        elections[ballot.election_id() as usize].ballots.push(BallotExport {
+            choices,
+            weight: ballot.weight(),
+            ..export_ballot(ballot.election_id(), ballot.fan())
+        });
+    }
+
+    VotesConfig {
+        admins: schema.admins().iter().collect(),
+        teams: schema.team_wallets()
+            .values()
+            .map(|wallet| TeamConfig { pub_key: *wallet.pub_key(), name: wallet.name().to_owned() })
+            .collect(),
+        election: None,
+        fans: schema.fan_wallets()
+            .values()
+            .map(|wallet| FanConfig {
+                pub_key: *wallet.pub_key(),
+                name: wallet.name().to_owned(),
+                delegate: if wallet.has_delegate() { Some(*wallet.delegate()) } else { None },
+            })
+            .collect(),
+        elections,
+    }
+}
+
+
+/// Wallet of either kind, as returned by the `/v1/wallet/:pub_key` lookup.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "wallet", rename_all = "snake_case")]
+pub enum WalletInfo {
+    Fan(FanWallet),
+    Team(TeamWallet),
+}
+
+
+/// Status of a transaction. Failures carry the block height and position of the
+/// transaction; `code`, `name` and `description` are decoded from `errors::Error`
+/// when the code is known, and `code` is `null` if the transaction panicked.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TransactionStatus {
+    Pending,
+    Success {
+        height: u64,
+        position: u64,
+    },
+    Failure {
+        height: u64,
+        position: u64,
+        code: Option<u8>,
+        name: Option<String>,
+        description: Option<String>,
+    },
+}
+
+
+/// Error code of the service with its meaning, for clients to localize messages.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorInfo {
+    pub code: u8,
+    pub name: String,
+    pub description: String,
+}
+
+
+impl From<Error> for ErrorInfo {
+    fn from(error: Error) -> Self {
+        ErrorInfo {
+            code: error as u8,
+            name: error.name(),
+            description: error.to_string(),
+        }
+    }
+}
+
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TeamRating {
+    pub name: String,
+    pub pub_key: PublicKey,
+    pub votes: u64,
+}
+
+
+/// Votes of a team at the end of the block at `height`.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct VotesPoint {
+    pub height: u64,
+    pub votes: u64,
+}
+
+
+/// Place of a team in the rating of an election, starting from zero.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TeamRank {
+    pub rank: u64,
+    pub votes: u64,
+}
+
+
 impl VotesApi {
+    fn pub_key_param(&self, req: &Request) -> IronResult<PublicKey> {
+        let params = req.extensions.get::<Router>().unwrap();
+        let wallet_key = params.find("pub_key").unwrap();
+        PublicKey::from_hex(wallet_key).map_err(|e| {
+            IronError::new(
+                e,
+                (
+                    Status::BadRequest,
+                    Header(ContentType::json()),
+                    "\"Invalid request param: `pub_key`\"",
+                ),
+            )
+        })
+    }
+
+    fn tx_hash_param(&self, req: &Request) -> IronResult<Hash> {
+        let params = req.extensions.get::<Router>().unwrap();
+        let tx_hash = params.find("hash").unwrap();
+        Hash::from_hex(tx_hash).map_err(|_| {
+            ApiError::BadRequest("Invalid request param: `hash`".into()).into()
+        })
+    }
+
+    fn election_id_path_param(&self, req: &Request) -> IronResult<u64> {
+        let params = req.extensions.get::<Router>().unwrap();
+        let election_id = params.find("election_id").unwrap();
+        election_id.parse().map_err(|_| {
+            ApiError::BadRequest("Invalid request param: `election_id`".into()).into()
+        })
+    }
+
+    fn query_param<T: FromStr>(&self, req: &Request, name: &str) -> IronResult<Option<T>> {
+        let value = req.url.as_ref()
+            .query_pairs()
+            .find(|&(ref key, _)| key == name)
+            .map(|(_, value)| value.into_owned());
+        match value {
+            Some(value) => value.parse().map(Some).map_err(|_| {
+                ApiError::BadRequest(format!("Invalid request param: `{}`", name)).into()
+            }),
+            None => Ok(None),
+        }
+    }
+
+    /// Page size given by the `limit` query param, at most `MAX_PAGE_SIZE`.
+    fn limit_param(&self, req: &Request) -> IronResult<u64> {
+        let limit = self.query_param(req, "limit")?.unwrap_or(DEFAULT_PAGE_SIZE);
+        Ok(limit.min(MAX_PAGE_SIZE))
+    }
+
+    fn pub_key_query_param(&self, req: &Request, name: &str) -> IronResult<Option<PublicKey>> {
+        match self.query_param::<String>(req, name)? {
+            Some(value) => PublicKey::from_hex(value).map(Some).map_err(|_| {
+                ApiError::BadRequest(format!("Invalid request param: `{}`", name)).into()
+            }),
+            None => Ok(None),
+        }
+    }
+
+    fn election_id_param(&self, req: &Request) -> IronResult<u64> {
+        match self.query_param(req, "election_id")? {
+            Some(election_id) => Ok(election_id),
+            None => Err(ApiError::BadRequest("Missing request param: `election_id`".into()))?,
+        }
+    }
+
+    fn latest_block_proof<T: AsRef<Snapshot>>(&self, schema: &Schema<T>) -> BlockProof {
+        schema.block_and_precommits(schema.height()).unwrap()
+    }
+
     fn post_transaction(&self, req: &mut Request) -> IronResult<Response> {
         match req.get::<bodyparser::Struct<Transactions>>() {
             Ok(Some(transaction)) => {
@@ -57,35 +405,75 @@ impl VotesApi {
         }
     }
 
-    fn get_fan_wallets(&self, _: &mut Request) -> IronResult<Response> {
+    fn post_transactions_batch(&self, req: &mut Request) -> IronResult<Response> {
+        let items = match req.get::<bodyparser::Struct<Vec<serde_json::Value>>>() {
+            Ok(Some(items)) => items,
+            Ok(None) => Err(ApiError::BadRequest("Empty request body".into()))?,
+            Err(e) => Err(ApiError::BadRequest(e.to_string()))?,
+        };
+        if items.len() > MAX_BATCH_SIZE {
+            Err(ApiError::BadRequest(
+                format!("Batch has more than {} transactions", MAX_BATCH_SIZE)
+            ))?
+        }
+        let results: Vec<BatchItemResponse> = items.into_iter()
+            .map(|item| {
+                let parsed: Result<Transactions, _> = serde_json::from_value(item);
+                let transaction: Box<Transaction> = match parsed {
+                    Ok(transaction) => transaction.into(),
+                    Err(e) => {
+                        return BatchItemResponse { tx_hash: None, error: Some(e.to_string()) };
+                    }
+                };
+                let tx_hash = transaction.hash();
+                let error = if !transaction.verify() {
+                    Some("Invalid signature".to_owned())
+                } else {
+                    self.channel.send(transaction).err().map(|e| e.to_string())
+                };
+                BatchItemResponse { tx_hash: Some(tx_hash), error }
+            })
+            .collect();
+        self.ok_response(&serde_json::to_value(&results).unwrap())
+    }
+
+    fn get_fan_wallets(&self, req: &mut Request) -> IronResult<Response> {
+        let from = self.pub_key_query_param(req, "from")?;
+        let limit = self.limit_param(req)?;
+        let name_prefix: Option<String> = self.query_param(req, "name")?;
+        let voted: Option<bool> = self.query_param(req, "voted")?;
+        let election_id = match voted {
+            Some(_) => Some(self.election_id_param(req)?),
+            None => None,
+        };
         let snapshot = self.blockchain.snapshot();
         let schema = VotesSchema::new(snapshot);
-        let idx = schema.fan_wallets();
-        let wallets: Vec<FanWallet> = idx.values().collect();
-        self.ok_response(&serde_json::to_value(&wallets).unwrap())
+        let page = wallets_page(&schema.fan_wallets(), from, limit, |wallet: &FanWallet| {
+            let name_matches = name_prefix.as_ref()
+                .map_or(true, |prefix| wallet.name().starts_with(prefix.as_str()));
+            let voted_matches = match (voted, election_id) {
+                (Some(voted), Some(election_id)) => {
+                    let ballot = schema.ballot(election_id, wallet.pub_key());
+                    ballot.map_or(false, |ballot| ballot.voted()) == voted
+                }
+                _ => true,
+            };
+            name_matches && voted_matches
+        });
+        self.ok_response(&serde_json::to_value(&page).unwrap())
     }
 
-    fn get_team_wallets(&self, _: &mut Request) -> IronResult<Response> {
+    fn get_team_wallets(&self, req: &mut Request) -> IronResult<Response> {
+        let from = self.pub_key_query_param(req, "from")?;
+        let limit = self.limit_param(req)?;
         let snapshot = self.blockchain.snapshot();
         let schema = VotesSchema::new(snapshot);
-        let idx = schema.team_wallets();
-        let wallets: Vec<TeamWallet> = idx.values().collect();
-        self.ok_response(&serde_json::to_value(&wallets).unwrap())
+        let page = wallets_page(&schema.team_wallets(), from, limit, |_: &TeamWallet| true);
+        self.ok_response(&serde_json::to_value(&page).unwrap())
     }
 
     fn get_fan_wallet(&self, req: &mut Request) -> IronResult<Response> {
-        let path = req.url.path();
-        let wallet_key = path.last().unwrap();
-        let public_key = PublicKey::from_hex(wallet_key).map_err(|e| {
-            IronError::new(
-                e,
-                (
-                    Status::BadRequest,
-                    Header(ContentType::json()),
-                    "\"Invalid request param: `pub_key`\"",
-                ),
-            )
-        })?;
+        let public_key = self.pub_key_param(req)?;
         let snapshot = self.blockchain.snapshot();
         let schema = VotesSchema::new(snapshot);
         if let Some(wallet) = schema.fan_wallet(&public_key) {
@@ -96,18 +484,7 @@ impl VotesApi {
     }
 
     fn get_team_wallet(&self, req: &mut Request) -> IronResult<Response> {
-        let path = req.url.path();
-        let wallet_key = path.last().unwrap();
-        let public_key = PublicKey::from_hex(wallet_key).map_err(|e| {
-            IronError::new(
-                e,
-                (
-                    Status::BadRequest,
-                    Header(ContentType::json()),
-                    "\"Invalid request param: `pub_key`\"",
-                ),
-            )
-        })?;
+        let public_key = self.pub_key_param(req)?;
         let snapshot = self.blockchain.snapshot();
         let schema = VotesSchema::new(snapshot);
         if let Some(wallet) = schema.team_wallet(&public_key) {
@@ -117,60 +494,354 @@ impl VotesApi {
         }
     }
 
-    fn get_rating(&self, _: &mut Request) -> IronResult<Response> {
+    fn get_wallet(&self, req: &mut Request) -> IronResult<Response> {
+        let public_key = self.pub_key_param(req)?;
         let snapshot = self.blockchain.snapshot();
         let schema = VotesSchema::new(snapshot);
-        let team_wallets = schema.team_wallets();
-        let mut teams_vec: Vec<TeamWallet> = team_wallets.into_iter().map(|x| { x.1 }).collect();
-        teams_vec.sort_by(|l, r| { r.votes().cmp(&l.votes()) });
-        self.ok_response(&serde_json::to_value(teams_vec).unwrap())
+        let wallet = match (schema.fan_wallet(&public_key), schema.team_wallet(&public_key)) {
+            (Some(wallet), _) => WalletInfo::Fan(wallet),
+            (None, Some(wallet)) => WalletInfo::Team(wallet),
+            (None, None) => {
+                return self.not_found_response(&serde_json::to_value("Wallet not found").unwrap());
+            }
+        };
+        self.ok_response(&serde_json::to_value(wallet).unwrap())
     }
 
-    fn get_block_by_fan_vote(&self, req: &mut Request) -> IronResult<Response> {
-        let path = req.url.path();
-        let wallet_key = path.last().unwrap();
-        let public_key = PublicKey::from_hex(wallet_key).map_err(|e| {
-            IronError::new(
-                e,
-                (
-                    Status::BadRequest,
-                    Header(ContentType::json()),
-                    "\"Invalid request param: `pub_key`\"",
-                ),
-            )
-        })?;
+    fn get_fan_wallet_proof(&self, req: &mut Request) -> IronResult<Response> {
+        let public_key = self.pub_key_param(req)?;
+        let snapshot = self.blockchain.snapshot();
+        let general_schema = Schema::new(&snapshot);
+        let schema = VotesSchema::new(&snapshot);
+        let proof = FanWalletProof {
+            block_proof: self.latest_block_proof(&general_schema),
+            to_table: general_schema.get_proof_to_service_table(SERVICE_ID, FAN_WALLETS_TABLE),
+            to_wallet: schema.fan_wallets().get_proof(public_key),
+        };
+        self.ok_response(&serde_json::to_value(proof).unwrap())
+    }
+
+    fn get_team_wallet_proof(&self, req: &mut Request) -> IronResult<Response> {
+        let public_key = self.pub_key_param(req)?;
+        let snapshot = self.blockchain.snapshot();
+        let general_schema = Schema::new(&snapshot);
+        let schema = VotesSchema::new(&snapshot);
+        let proof = TeamWalletProof {
+            block_proof: self.latest_block_proof(&general_schema),
+            to_table: general_schema.get_proof_to_service_table(SERVICE_ID, TEAM_WALLETS_TABLE),
+            to_wallet: schema.team_wallets().get_proof(public_key),
+        };
+        self.ok_response(&serde_json::to_value(proof).unwrap())
+    }
+
+    fn get_tally_proof(&self, req: &mut Request) -> IronResult<Response> {
+        let public_key = self.pub_key_param(req)?;
+        let election_id = self.election_id_param(req)?;
+        let snapshot = self.blockchain.snapshot();
+        let general_schema = Schema::new(&snapshot);
+        let schema = VotesSchema::new(&snapshot);
+        let proof = TallyProof {
+            block_proof: self.latest_block_proof(&general_schema),
+            to_table: general_schema.get_proof_to_service_table(SERVICE_ID, TALLIES_TABLE),
+            to_tally: schema.tallies().get_proof(election_key(election_id, &public_key)),
+        };
+        self.ok_response(&serde_json::to_value(proof).unwrap())
+    }
+
+    fn get_voters(&self, req: &mut Request) -> IronResult<Response> {
+        let public_key = self.pub_key_param(req)?;
+        let election_id = self.election_id_param(req)?;
+        let from = self.query_param(req, "from")?.unwrap_or(0);
+        let limit = self.limit_param(req)?;
+        let snapshot = self.blockchain.snapshot();
+        let general_schema = Schema::new(&snapshot);
+        let schema = VotesSchema::new(&snapshot);
+        if schema.tally(election_id, &public_key).is_none() {
+            return self.not_found_response(&serde_json::to_value("Tally not found").unwrap());
+        }
+        let voters = schema.voters(election_id, &public_key);
+        let to = voters.len().min(from.saturating_add(limit));
+        let proof = VotersProof {
+            tally_proof: TallyProof {
+                block_proof: self.latest_block_proof(&general_schema),
+                to_table: general_schema.get_proof_to_service_table(SERVICE_ID, TALLIES_TABLE),
+                to_tally: schema.tallies().get_proof(election_key(election_id, &public_key)),
+            },
+            to_voters: if from < to { Some(voters.get_range_proof(from, to)) } else { None },
+        };
+        self.ok_response(&serde_json::to_value(proof).unwrap())
+    }
+
+    fn get_votes_log(&self, req: &mut Request) -> IronResult<Response> {
+        let from = self.query_param(req, "from")?.unwrap_or(0);
+        let limit = self.limit_param(req)?;
+        let snapshot = self.blockchain.snapshot();
+        let general_schema = Schema::new(&snapshot);
+        let schema = VotesSchema::new(&snapshot);
+        let votes_log = schema.votes_log();
+        let to = votes_log.len().min(from.saturating_add(limit));
+        let proof = VotesLogProof {
+            block_proof: self.latest_block_proof(&general_schema),
+            to_table: general_schema.get_proof_to_service_table(SERVICE_ID, VOTES_LOG_TABLE),
+            len: votes_log.len(),
+            to_votes: if from < to { Some(votes_log.get_range_proof(from, to)) } else { None },
+        };
+        self.ok_response(&serde_json::to_value(proof).unwrap())
+    }
+
+    fn get_elections(&self, _: &mut Request) -> IronResult<Response> {
+        let snapshot = self.blockchain.snapshot();
+        let schema = VotesSchema::new(snapshot);
+        let elections: Vec<Election> = schema.elections().iter().collect();
+        self.ok_response(&serde_json::to_value(&elections).unwrap())
+    }
+
+    fn get_election(&self, req: &mut Request) -> IronResult<Response> {
+        let election_id = self.election_id_path_param(req)?;
+        let snapshot = self.blockchain.snapshot();
+        let schema = VotesSchema::new(snapshot);
+        if let Some(election) = schema.election(election_id) {
+            self.ok_response(&serde_json::to_value(election).unwrap())
+        } else {
+            self.not_found_response(&serde_json::to_value("Election not found").unwrap())
+        }
+    }
+
+    fn get_unrevealed(&self, req: &mut Request) -> IronResult<Response> {
+        let election_id = self.election_id_path_param(req)?;
+        let snapshot = self.blockchain.snapshot();
+        let schema = VotesSchema::new(snapshot);
+        if schema.election(election_id).is_none() {
+            return self.not_found_response(&serde_json::to_value("Election not found").unwrap());
+        }
+        self.ok_response(&serde_json::to_value(schema.unrevealed_fans(election_id)).unwrap())
+    }
+
+    fn get_commitment(&self, req: &mut Request) -> IronResult<Response> {
+        let public_key = self.pub_key_param(req)?;
+        let election_id = self.election_id_param(req)?;
+        let snapshot = self.blockchain.snapshot();
+        let schema = VotesSchema::new(snapshot);
+        if let Some(commitment) = schema.commitment(election_id, &public_key) {
+            self.ok_response(&serde_json::to_value(commitment).unwrap())
+        } else {
+            self.not_found_response(&serde_json::to_value("Commitment not found").unwrap())
+        }
+    }
+
+    fn get_ballot(&self, req: &mut Request) -> IronResult<Response> {
+        let public_key = self.pub_key_param(req)?;
+        let election_id = self.election_id_param(req)?;
+        let snapshot = self.blockchain.snapshot();
+        let schema = VotesSchema::new(snapshot);
+        if schema.election(election_id).is_none() {
+            return self.not_found_response(&serde_json::to_value("Election not found").unwrap());
+        }
+        if schema.fan_wallet(&public_key).is_none() {
+            return self.not_found_response(&serde_json::to_value("Fan wallet not found").unwrap());
+        }
+        let ballot = schema.ballot(election_id, &public_key)
+            .unwrap_or_else(|| Ballot::empty(election_id, &public_key));
+        self.ok_response(&serde_json::to_value(BallotInfo::from(ballot)).unwrap())
+    }
+
+    fn get_ballot_history(&self, req: &mut Request) -> IronResult<Response> {
+        let public_key = self.pub_key_param(req)?;
+        let election_id = self.election_id_param(req)?;
+        let snapshot = self.blockchain.snapshot();
+        let schema = VotesSchema::new(snapshot);
+        let history: Vec<Hash> = schema.ballot_history(election_id, &public_key).iter().collect();
+        self.ok_response(&serde_json::to_value(history).unwrap())
+    }
+
+    fn get_rating(&self, req: &mut Request) -> IronResult<Response> {
+        let election_id = self.election_id_param(req)?;
+        let method = self.query_param(req, "method")?.unwrap_or(TallyMethod::Plurality);
+        let from = self.query_param(req, "from")?.unwrap_or(0);
+        let limit = self.limit_param(req)?;
+        let min_votes = self.query_param(req, "min_votes")?.unwrap_or(0);
+        let max_votes = self.query_param(req, "max_votes")?.unwrap_or_else(u64::max_value);
+        let height: Option<u64> = self.query_param(req, "height")?;
         let snapshot = self.blockchain.snapshot();
+        let latest_height = Schema::new(&snapshot).height().0;
         let schema = VotesSchema::new(snapshot);
-        let fan_wallet = match schema.fan_wallet(&public_key) {
+        let election = match schema.election(election_id) {
             Some(x) => x,
-            None => return self.not_found_response(&serde_json::to_value("Wallet not found").unwrap())
+            None => return self.not_found_response(&serde_json::to_value("Election not found").unwrap())
         };
-        if !fan_wallet.voted() {
-            return self.not_found_response(&serde_json::to_value("Fan not voted yet").unwrap());
+        let page = |results: Vec<(PublicKey, u64)>| -> Vec<(PublicKey, u64)> {
+            results.into_iter().skip(from as usize).take(limit as usize).collect()
         };
-        let blockchain_explorer = BlockchainExplorer::new(&self.blockchain);
-        let tx_hash = Hash::from_str(fan_wallet.vote_hash()).map_err(|e| {
-            IronError::new(
-                e,
-                (
-                    Status::BadRequest,
-                    Header(ContentType::json()),
-                    "\"Cannot convert string to hash\"",
+        let results: Vec<(PublicKey, u64)> = match (method, height) {
+            (TallyMethod::Plurality, None) => schema
+                .rating(election_id, min_votes, max_votes, from, limit)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|tally| (*tally.team(), tally.votes()))
+                .collect(),
+            (TallyMethod::Plurality, Some(height)) if height > latest_height => Err(
+                ApiError::BadRequest("Block at the height is not committed yet".into())
+            )?,
+            (TallyMethod::Plurality, Some(height)) => match schema.rating_at(election_id, height) {
+                Some(rating) => page(rating
+                    .into_iter()
+                    .map(|tally| (*tally.team(), tally.votes()))
+                    .filter(|&(_, votes)| votes >= min_votes && votes <= max_votes)
+                    .collect()),
+                None => return self.not_found_response(
+                    &serde_json::to_value("Election not found").unwrap()
                 ),
-            )
-        })?;
-        if let Some(info) = blockchain_explorer.transaction(&tx_hash) {
-            let tx_info = match info {
-                Committed(x) => x,
-                _ => return self.not_found_response(
-                    &serde_json::to_value("Transaction not yet committed").unwrap()
-                )
-            };
-            let block_height = tx_info.location().block_height();
-            self.ok_response(&serde_json::to_value(blockchain_explorer.block(block_height).unwrap().header()).unwrap())
+            },
+            (_, Some(_)) => Err(ApiError::BadRequest(
+                "Rating at a height is available only for plurality".into()
+            ))?,
+            _ if election.mode() != BallotMode::Ranked => Err(ApiError::BadRequest(
+                "Election doesn't have ranked ballots".into()
+            ))?,
+            (TallyMethod::Borda, None) => page(tally::borda(
+                &election.candidates(), &schema.election_rankings(election_id)
+            )),
+            (TallyMethod::InstantRunoff, None) => page(tally::instant_runoff(
+                &election.candidates(), &schema.election_rankings(election_id)
+            )),
+        };
+        let rating: Vec<TeamRating> = results.into_iter()
+            .filter_map(|(team, votes)| {
+                schema.team_wallet(&team).map(|wallet| TeamRating {
+                    name: wallet.name().to_owned(),
+                    pub_key: team,
+                    votes,
+                })
+            })
+            .collect();
+        self.ok_response(&serde_json::to_value(rating).unwrap())
+    }
+
+    fn get_team_history(&self, req: &mut Request) -> IronResult<Response> {
+        let public_key = self.pub_key_param(req)?;
+        let election_id = self.election_id_param(req)?;
+        let step: u64 = self.query_param(req, "step")?.unwrap_or(1);
+        if step == 0 {
+            Err(ApiError::BadRequest("Invalid request param: `step`".into()))?
+        }
+        let snapshot = self.blockchain.snapshot();
+        let schema = VotesSchema::new(snapshot);
+        if schema.tally(election_id, &public_key).is_none() {
+            return self.not_found_response(&serde_json::to_value("Team is not a candidate").unwrap());
+        }
+        // Only the last point of every `step` blocks is kept.
+        let mut points: Vec<VotesPoint> = Vec::new();
+        for (height, votes) in schema.team_history(election_id, &public_key) {
+            match points.last_mut() {
+                Some(ref mut last) if last.height / step == height / step => {
+                    last.height = height;
+                    last.votes = votes;
+                }
+                _ => points.push(VotesPoint { height, votes }),
+            }
+        }
+        self.ok_response(&serde_json::to_value(points).unwrap())
+    }
+
+    fn get_team_rank(&self, req: &mut Request) -> IronResult<Response> {
+        let public_key = self.pub_key_param(req)?;
+        let election_id = self.election_id_param(req)?;
+        let snapshot = self.blockchain.snapshot();
+        let schema = VotesSchema::new(snapshot);
+        match (schema.tally(election_id, &public_key), schema.team_rank(election_id, &public_key)) {
+            (Some(tally), Some(rank)) => {
+                let json = TeamRank { rank, votes: tally.votes() };
+                self.ok_response(&serde_json::to_value(json).unwrap())
+            }
+            _ => self.not_found_response(&serde_json::to_value("Team is not a candidate").unwrap()),
+        }
+    }
+
+    fn get_ranking(&self, req: &mut Request) -> IronResult<Response> {
+        let public_key = self.pub_key_param(req)?;
+        let election_id = self.election_id_param(req)?;
+        let snapshot = self.blockchain.snapshot();
+        let schema = VotesSchema::new(snapshot);
+        if let Some(ranking) = schema.ranking(election_id, &public_key) {
+            self.ok_response(&serde_json::to_value(ranking).unwrap())
         } else {
-            self.not_found_response(&serde_json::to_value("Transaction not found").unwrap())
+            self.not_found_response(&serde_json::to_value("Ranking not found").unwrap())
+        }
+    }
+
+    fn get_block_by_fan_vote(&self, req: &mut Request) -> IronResult<Response> {
+        let public_key = self.pub_key_param(req)?;
+        let election_id = self.election_id_param(req)?;
+        let snapshot = self.blockchain.snapshot();
+        let schema = VotesSchema::new(snapshot);
+        if schema.fan_wallet(&public_key).is_none() {
+            return self.not_found_response(&serde_json::to_value("Wallet not found").unwrap());
         }
+        let ballot = match schema.ballot(election_id, &public_key) {
+            Some(ref x) if x.voted() => x.clone(),
+            _ => return self.not_found_response(&serde_json::to_value("Fan not voted yet").unwrap())
+        };
+        let blockchain_explorer = BlockchainExplorer::new(&self.blockchain);
+        match blockchain_explorer.block(Height(ballot.vote_height())) {
+            Some(block) => self.ok_response(&serde_json::to_value(block.header()).unwrap()),
+            None => self.not_found_response(&serde_json::to_value("Block not found").unwrap()),
+        }
+    }
+
+    fn get_transaction_status(&self, req: &mut Request) -> IronResult<Response> {
+        let tx_hash = self.tx_hash_param(req)?;
+        let blockchain_explorer = BlockchainExplorer::new(&self.blockchain);
+        let status = match blockchain_explorer.transaction(&tx_hash) {
+            Some(TransactionInfo::InPool { .. }) => TransactionStatus::Pending,
+            Some(TransactionInfo::Committed(tx)) => {
+                let height = tx.location().block_height().0;
+                let position = tx.location().position_in_block();
+                match tx.status() {
+                    Ok(()) => TransactionStatus::Success { height, position },
+                    Err(e) => match e.error_type() {
+                        TransactionErrorType::Code(code) => match Error::from_code(code) {
+                            Some(error) => TransactionStatus::Failure {
+                                height,
+                                position,
+                                code: Some(code),
+                                name: Some(error.name()),
+                                description: Some(error.to_string()),
+                            },
+                            None => TransactionStatus::Failure {
+                                height,
+                                position,
+                                code: Some(code),
+                                name: None,
+                                description: e.description().map(str::to_owned),
+                            },
+                        },
+                        TransactionErrorType::Panic => TransactionStatus::Failure {
+                            height,
+                            position,
+                            code: None,
+                            name: None,
+                            description: e.description().map(str::to_owned),
+                        },
+                    },
+                }
+            }
+            None => {
+                let message = "Transaction not found";
+                return self.not_found_response(&serde_json::to_value(message).unwrap());
+            }
+        };
+        self.ok_response(&serde_json::to_value(status).unwrap())
+    }
+
+    fn get_export(&self, _: &mut Request) -> IronResult<Response> {
+        let snapshot = self.blockchain.snapshot();
+        let schema = VotesSchema::new(snapshot);
+        self.ok_response(&serde_json::to_value(export_config(&schema)).unwrap())
+    }
+
+    fn get_errors(&self, _: &mut Request) -> IronResult<Response> {
+        let errors: Vec<ErrorInfo> = Error::all().into_iter().map(ErrorInfo::from).collect();
+        self.ok_response(&serde_json::to_value(errors).unwrap())
     }
 }
 
@@ -180,8 +851,28 @@ impl Api for VotesApi {
         let self_ = self.clone();
         let post_create_wallet = move |req: &mut Request| self_.post_transaction(req);
         let self_ = self.clone();
+        let post_register_team = move |req: &mut Request| self_.post_transaction(req);
+        let self_ = self.clone();
         let post_vote = move |req: &mut Request| self_.post_transaction(req);
         let self_ = self.clone();
+        let post_change_vote = move |req: &mut Request| self_.post_transaction(req);
+        let self_ = self.clone();
+        let post_revoke_vote = move |req: &mut Request| self_.post_transaction(req);
+        let self_ = self.clone();
+        let post_multi_vote = move |req: &mut Request| self_.post_transaction(req);
+        let self_ = self.clone();
+        let post_ranked_vote = move |req: &mut Request| self_.post_transaction(req);
+        let self_ = self.clone();
+        let post_commit_vote = move |req: &mut Request| self_.post_transaction(req);
+        let self_ = self.clone();
+        let post_reveal_vote = move |req: &mut Request| self_.post_transaction(req);
+        let self_ = self.clone();
+        let post_delegate = move |req: &mut Request| self_.post_transaction(req);
+        let self_ = self.clone();
+        let post_undelegate = move |req: &mut Request| self_.post_transaction(req);
+        let self_ = self.clone();
+        let post_transactions_batch = move |req: &mut Request| self_.post_transactions_batch(req);
+        let self_ = self.clone();
         let get_fan_wallets = move |req: &mut Request| self_.get_fan_wallets(req);
         let self_ = self.clone();
         let get_fan_wallet = move |req: &mut Request| self_.get_fan_wallet(req);
@@ -190,17 +881,84 @@ impl Api for VotesApi {
         let self_ = self.clone();
         let get_team_wallet = move |req: &mut Request| self_.get_team_wallet(req);
         let self_ = self.clone();
+        let get_wallet = move |req: &mut Request| self_.get_wallet(req);
+        let self_ = self.clone();
+        let get_fan_wallet_proof = move |req: &mut Request| self_.get_fan_wallet_proof(req);
+        let self_ = self.clone();
+        let get_team_wallet_proof = move |req: &mut Request| self_.get_team_wallet_proof(req);
+        let self_ = self.clone();
+        let get_tally_proof = move |req: &mut Request| self_.get_tally_proof(req);
+        let self_ = self.clone();
+        let get_voters = move |req: &mut Request| self_.get_voters(req);
+        let self_ = self.clone();
+        let post_create_election = move |req: &mut Request| self_.post_transaction(req);
+        let self_ = self.clone();
+        let get_elections = move |req: &mut Request| self_.get_elections(req);
+        let self_ = self.clone();
+        let get_election = move |req: &mut Request| self_.get_election(req);
+        let self_ = self.clone();
+        let get_ballot = move |req: &mut Request| self_.get_ballot(req);
+        let self_ = self.clone();
+        let get_ballot_history = move |req: &mut Request| self_.get_ballot_history(req);
+        let self_ = self.clone();
+        let get_ranking = move |req: &mut Request| self_.get_ranking(req);
+        let self_ = self.clone();
+        let get_commitment = move |req: &mut Request| self_.get_commitment(req);
+        let self_ = self.clone();
+        let get_unrevealed = move |req: &mut Request| self_.get_unrevealed(req);
+        let self_ = self.clone();
         let get_rating = move |req: &mut Request| self_.get_rating(req);
         let self_ = self.clone();
+        let get_team_rank = move |req: &mut Request| self_.get_team_rank(req);
+        let self_ = self.clone();
+        let get_team_history = move |req: &mut Request| self_.get_team_history(req);
+        let self_ = self.clone();
         let get_block = move |req: &mut Request| self_.get_block_by_fan_vote(req);
+        let self_ = self.clone();
+        let get_votes_log = move |req: &mut Request| self_.get_votes_log(req);
+        let self_ = self.clone();
+        let get_errors = move |req: &mut Request| self_.get_errors(req);
+        let self_ = self.clone();
+        let get_export = move |req: &mut Request| self_.get_export(req);
+        let self_ = self.clone();
+        let get_transaction_status = move |req: &mut Request| self_.get_transaction_status(req);
 
         router.post("/v1/create", post_create_wallet, "post_create_wallet");
+        router.post("/v1/team/register", post_register_team, "post_register_team");
         router.post("/v1/vote", post_vote, "post_vote");
+        router.post("/v1/change_vote", post_change_vote, "post_change_vote");
+        router.post("/v1/revoke_vote", post_revoke_vote, "post_revoke_vote");
+        router.post("/v1/multi_vote", post_multi_vote, "post_multi_vote");
+        router.post("/v1/ranked_vote", post_ranked_vote, "post_ranked_vote");
+        router.post("/v1/commit_vote", post_commit_vote, "post_commit_vote");
+        router.post("/v1/reveal_vote", post_reveal_vote, "post_reveal_vote");
+        router.post("/v1/delegate", post_delegate, "post_delegate");
+        router.post("/v1/undelegate", post_undelegate, "post_undelegate");
+        router.post("/v1/transactions/batch", post_transactions_batch, "post_transactions_batch");
         router.get("/v1/fan/wallets", get_fan_wallets, "get_fan_wallets");
         router.get("/v1/fan/wallet/:pub_key", get_fan_wallet, "get_fan_wallet");
         router.get("/v1/team/wallets", get_team_wallets, "get_team_wallets");
         router.get("/v1/team/wallet/:pub_key", get_team_wallet, "get_team_wallet");
+        router.get("/v1/wallet/:pub_key", get_wallet, "get_wallet");
+        router.get("/v1/fan/wallet/:pub_key/proof", get_fan_wallet_proof, "get_fan_wallet_proof");
+        router.get("/v1/team/wallet/:pub_key/proof", get_team_wallet_proof, "get_team_wallet_proof");
+        router.get("/v1/team/wallet/:pub_key/tally/proof", get_tally_proof, "get_tally_proof");
+        router.get("/v1/team/wallet/:pub_key/voters", get_voters, "get_voters");
+        router.post("/v1/election/create", post_create_election, "post_create_election");
+        router.get("/v1/elections", get_elections, "get_elections");
+        router.get("/v1/election/:election_id", get_election, "get_election");
+        router.get("/v1/fan/wallet/:pub_key/ballot", get_ballot, "get_ballot");
+        router.get("/v1/fan/wallet/:pub_key/ballot/history", get_ballot_history, "get_ballot_history");
+        router.get("/v1/fan/wallet/:pub_key/ranking", get_ranking, "get_ranking");
+        router.get("/v1/fan/wallet/:pub_key/commitment", get_commitment, "get_commitment");
+        router.get("/v1/election/:election_id/unrevealed", get_unrevealed, "get_unrevealed");
         router.get("/v1/rating", get_rating, "get_rating");
+        router.get("/v1/team/wallet/:pub_key/rank", get_team_rank, "get_team_rank");
+        router.get("/v1/team/wallet/:pub_key/history", get_team_history, "get_team_history");
         router.get("/v1/block/:pub_key", get_block, "get_block");
+        router.get("/v1/votes/log", get_votes_log, "get_votes_log");
+        router.get("/v1/errors", get_errors, "get_errors");
+        router.get("/v1/export", get_export, "get_export");
+        router.get("/v1/transaction/:hash", get_transaction_status, "get_transaction_status");
     }
 }
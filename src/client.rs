@@ -0,0 +1,174 @@
+//! Offline verification of proof-carrying API responses.
+//!
+//! A client that knows the consensus keys of the validators can check the
+//! answers of `/v1/fan/wallet/:pub_key/proof`, `/v1/team/wallet/:pub_key/proof`,
+//! `/v1/team/wallet/:pub_key/tally/proof`, `/v1/team/wallet/:pub_key/voters`
+//! and `/v1/votes/log` without trusting the node that served them.
+
+use exonum::{blockchain::{Block, BlockProof, Blockchain},
+             crypto::{CryptoHash, Hash, PublicKey},
+             messages::Message,
+             storage::{MapProof, ProofMapKey, StorageValue}};
+use serde_json::{self, Value};
+
+use api::{FanWalletProof, TeamWalletProof, TallyProof, VotersProof, VotesLogProof};
+use constants::{SERVICE_ID, FAN_WALLETS_TABLE, TEAM_WALLETS_TABLE, TALLIES_TABLE,
+                VOTES_LOG_TABLE};
+use election::{Tally, Voter};
+use schema::election_key;
+use wallet::{FanWallet, TeamWallet};
+
+
+#[derive(Debug, Fail)]
+pub enum Error {
+    #[fail(display = "Malformed proof: {}", _0)]
+    MalformedProof(String),
+
+    #[fail(display = "Block is not confirmed by a majority of validators")]
+    InvalidBlockProof,
+
+    #[fail(display = "Service table proof doesn't match the block state hash")]
+    InvalidTableProof,
+
+    #[fail(display = "Entry proof doesn't match the service table root")]
+    InvalidEntryProof,
+
+    #[fail(display = "Entry not found")]
+    EntryNotFound,
+}
+
+
+/// Verifies the answer of `/v1/fan/wallet/:pub_key/proof` against
+/// the consensus keys of the validators and returns the proven wallet.
+pub fn verify_fan_wallet(response: Value, pub_key: &PublicKey, validators: &[PublicKey])
+                         -> Result<FanWallet, Error> {
+    let proof: FanWalletProof = serde_json::from_value(response)
+        .map_err(|e| Error::MalformedProof(e.to_string()))?;
+    verify_block(&proof.block_proof, validators)?;
+    let table_root = verify_table(proof.to_table, &proof.block_proof.block, FAN_WALLETS_TABLE)?;
+    verify_entry(proof.to_wallet, pub_key, table_root)
+}
+
+
+/// Verifies the answer of `/v1/team/wallet/:pub_key/proof` against
+/// the consensus keys of the validators and returns the proven wallet.
+pub fn verify_team_wallet(response: Value, pub_key: &PublicKey, validators: &[PublicKey])
+                          -> Result<TeamWallet, Error> {
+    let proof: TeamWalletProof = serde_json::from_value(response)
+        .map_err(|e| Error::MalformedProof(e.to_string()))?;
+    verify_block(&proof.block_proof, validators)?;
+    let table_root = verify_table(proof.to_table, &proof.block_proof.block, TEAM_WALLETS_TABLE)?;
+    verify_entry(proof.to_wallet, pub_key, table_root)
+}
+
+
+/// Verifies the answer of `/v1/team/wallet/:pub_key/tally/proof` against
+/// the consensus keys of the validators and returns the proven tally.
+pub fn verify_tally(response: Value, election_id: u64, team: &PublicKey, validators: &[PublicKey])
+                    -> Result<Tally, Error> {
+    let proof: TallyProof = serde_json::from_value(response)
+        .map_err(|e| Error::MalformedProof(e.to_string()))?;
+    verify_tally_proof(proof, election_id, team, validators)
+}
+
+
+/// Verifies the answer of `/v1/team/wallet/:pub_key/voters` against
+/// the consensus keys of the validators and returns the proven tally
+/// together with the proven page of its voters.
+pub fn verify_voters(response: Value, election_id: u64, team: &PublicKey, validators: &[PublicKey])
+                     -> Result<(Tally, Vec<Voter>), Error> {
+    let proof: VotersProof = serde_json::from_value(response)
+        .map_err(|e| Error::MalformedProof(e.to_string()))?;
+    let tally = verify_tally_proof(proof.tally_proof, election_id, team, validators)?;
+    let voters = match proof.to_voters {
+        Some(to_voters) => to_voters.validate(*tally.voters_hash(), tally.voters_len())
+            .map_err(|_| Error::InvalidEntryProof)?
+            .into_iter()
+            .map(|(_, voter)| voter.clone())
+            .collect(),
+        None => Vec::new(),
+    };
+    Ok((tally, voters))
+}
+
+
+/// Verifies the answer of `/v1/votes/log` against the consensus keys
+/// of the validators and returns the proven entries with their positions in the log.
+pub fn verify_votes_log(response: Value, validators: &[PublicKey]) -> Result<Vec<(u64, Hash)>, Error> {
+    let proof: VotesLogProof = serde_json::from_value(response)
+        .map_err(|e| Error::MalformedProof(e.to_string()))?;
+    verify_block(&proof.block_proof, validators)?;
+    let table_root = verify_table(proof.to_table, &proof.block_proof.block, VOTES_LOG_TABLE)?;
+    match proof.to_votes {
+        Some(to_votes) => Ok(to_votes.validate(table_root, proof.len)
+            .map_err(|_| Error::InvalidEntryProof)?
+            .into_iter()
+            .map(|(i, tx_hash)| (i, *tx_hash))
+            .collect()),
+        None => Ok(Vec::new()),
+    }
+}
+
+
+fn verify_tally_proof(proof: TallyProof, election_id: u64, team: &PublicKey, validators: &[PublicKey])
+                      -> Result<Tally, Error> {
+    verify_block(&proof.block_proof, validators)?;
+    let table_root = verify_table(proof.to_table, &proof.block_proof.block, TALLIES_TABLE)?;
+    verify_entry(proof.to_tally, &election_key(election_id, team), table_root)
+}
+
+
+/// Checks that the block is signed by more than 2/3 of the validators.
+fn verify_block(block_proof: &BlockProof, validators: &[PublicKey]) -> Result<(), Error> {
+    let block = &block_proof.block;
+    let block_hash = block.hash();
+    let mut confirmed: Vec<usize> = Vec::new();
+    for precommit in &block_proof.precommits {
+        let validator = precommit.validator().0 as usize;
+        let consensus_key = validators.get(validator).ok_or(Error::InvalidBlockProof)?;
+        if precommit.height() != block.height() || *precommit.block_hash() != block_hash
+            || !precommit.verify_signature(consensus_key) {
+            return Err(Error::InvalidBlockProof);
+        }
+        if !confirmed.contains(&validator) {
+            confirmed.push(validator);
+        }
+    }
+    if confirmed.len() < validators.len() * 2 / 3 + 1 {
+        return Err(Error::InvalidBlockProof);
+    }
+    Ok(())
+}
+
+
+/// Checks the proof of the service table against the block state hash
+/// and returns the root of the table.
+fn verify_table(proof: MapProof<Hash, Hash>, block: &Block, table: usize) -> Result<Hash, Error> {
+    let checked = proof.check().map_err(|_| Error::InvalidTableProof)?;
+    if checked.merkle_root() != *block.state_hash() {
+        return Err(Error::InvalidTableProof);
+    }
+    let table_key = Blockchain::service_table_unique_key(SERVICE_ID, table);
+    checked.entries()
+        .into_iter()
+        .find(|&(key, _)| *key == table_key)
+        .map(|(_, root)| *root)
+        .ok_or(Error::InvalidTableProof)
+}
+
+
+/// Checks the proof of a table entry against the table root and returns the entry.
+fn verify_entry<K, V>(proof: MapProof<K, V>, key: &K, table_root: Hash) -> Result<V, Error>
+    where K: ProofMapKey + PartialEq,
+          V: StorageValue + Clone
+{
+    let checked = proof.check().map_err(|_| Error::InvalidEntryProof)?;
+    if checked.merkle_root() != table_root {
+        return Err(Error::InvalidEntryProof);
+    }
+    checked.entries()
+        .into_iter()
+        .find(|&(entry_key, _)| entry_key == key)
+        .map(|(_, value)| value.clone())
+        .ok_or(Error::EntryNotFound)
+}
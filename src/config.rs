@@ -0,0 +1,88 @@
+//! Service configuration stored in the genesis block.
+
+use exonum::crypto::{Hash, PublicKey};
+
+use election::BallotMode;
+
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TeamConfig {
+    pub pub_key: PublicKey,
+    pub name: String,
+}
+
+
+/// Election opened in the genesis block for all initial teams.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ElectionConfig {
+    pub title: String,
+    pub start_height: u64,
+    pub end_height: u64,
+    pub max_votes_per_fan: u64,
+    #[serde(default)]
+    pub ballot_mode: BallotMode,
+    /// Non-zero for secret elections, see `Election`.
+    #[serde(default)]
+    pub reveal_height: u64,
+}
+
+
+/// Fan registered in the genesis block, e.g. exported from another network.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FanConfig {
+    pub pub_key: PublicKey,
+    pub name: String,
+    /// Fan to whom the vote is delegated.
+    #[serde(default)]
+    pub delegate: Option<PublicKey>,
+}
+
+
+/// Vote of a fan in an exported election. Plain ballots list `choices`,
+/// ranked ones the `ranking`; a secret vote keeps its `commitment` and has
+/// no choices until it is revealed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BallotExport {
+    pub fan: PublicKey,
+    #[serde(default)]
+    pub choices: Vec<(PublicKey, u64)>,
+    #[serde(default)]
+    pub ranking: Vec<PublicKey>,
+    /// Number of times each vote counts, see `Ballot`.
+    pub weight: u64,
+    /// Fans whose votes are carried by the ballot.
+    #[serde(default)]
+    pub delegators: Vec<PublicKey>,
+    #[serde(default)]
+    pub commitment: Option<Hash>,
+}
+
+
+/// Election with the votes cast in it, as returned by `/v1/export`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ElectionExport {
+    pub title: String,
+    pub candidates: Vec<PublicKey>,
+    pub start_height: u64,
+    pub end_height: u64,
+    pub max_votes: u64,
+    pub ballot_mode: BallotMode,
+    pub reveal_height: u64,
+    pub ballots: Vec<BallotExport>,
+}
+
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VotesConfig {
+    /// Keys allowed to register teams.
+    pub admins: Vec<PublicKey>,
+    /// Teams registered in the genesis block.
+    pub teams: Vec<TeamConfig>,
+    pub election: Option<ElectionConfig>,
+    /// Fans registered in the genesis block.
+    #[serde(default)]
+    pub fans: Vec<FanConfig>,
+    /// Elections restored with their votes after `election`.
+    #[serde(default)]
+    pub elections: Vec<ElectionExport>,
+}
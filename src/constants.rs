@@ -1,2 +1,21 @@
 pub const SERVICE_NAME: &'static str = "football_voting";
 pub const SERVICE_ID: u16 = 1u16;
+
+/// Positions of the service tables in `VotesSchema::state_hash`.
+pub const FAN_WALLETS_TABLE: usize = 0;
+pub const TEAM_WALLETS_TABLE: usize = 1;
+pub const ELECTIONS_TABLE: usize = 2;
+pub const BALLOTS_TABLE: usize = 3;
+pub const TALLIES_TABLE: usize = 4;
+pub const ADMINS_TABLE: usize = 5;
+pub const VOTES_LOG_TABLE: usize = 6;
+pub const RANKINGS_TABLE: usize = 7;
+pub const COMMITMENTS_TABLE: usize = 8;
+pub const HISTORY_ROOTS_TABLE: usize = 9;
+
+/// Number of entries returned by paginated endpoints unless `limit` is given.
+pub const DEFAULT_PAGE_SIZE: u64 = 100;
+/// Larger `limit` values of paginated endpoints are reduced to this one.
+pub const MAX_PAGE_SIZE: u64 = 1000;
+/// Transaction batches with more items are rejected as a whole.
+pub const MAX_BATCH_SIZE: usize = MAX_PAGE_SIZE as usize;
@@ -0,0 +1,315 @@
+use exonum::crypto::{self, Hash, PublicKey};
+
+
+/// How fans cast their votes in an election.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum BallotMode {
+    /// Every `TxVote` gives one vote to one team.
+    Single = 0,
+    /// `TxMultiVote` spreads several votes across several teams at once.
+    Points = 1,
+    /// `TxRankedVote` orders the candidates by preference.
+    Ranked = 2,
+}
+
+
+impl BallotMode {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(BallotMode::Single),
+            1 => Some(BallotMode::Points),
+            2 => Some(BallotMode::Ranked),
+            _ => None,
+        }
+    }
+}
+
+
+impl Default for BallotMode {
+    fn default() -> Self {
+        BallotMode::Single
+    }
+}
+
+
+encoding_struct! {
+    /// Election of a winner among the candidates. Each fan has `max_votes` votes,
+    /// which are cast as set by `ballot_mode` (see `BallotMode`).
+    ///
+    /// Votes of a secret election (with non-zero `reveal_height`) are committed
+    /// before `reveal_height` and revealed starting from it. `created_height` is
+    /// the height of the block which added the election.
+    struct Election {
+        id: u64,
+        title: &str,
+        candidates: Vec<PublicKey>,
+        start_height: u64,
+        end_height: u64,
+        max_votes: u64,
+        ballot_mode: u8,
+        reveal_height: u64,
+        created_height: u64,
+    }
+}
+
+
+encoding_struct! {
+    /// Vote of a fan in a secret election, see `vote_commitment`.
+    struct Commitment {
+        election_id: u64,
+        fan: &PublicKey,
+        commitment: &Hash,
+        revealed: bool,
+    }
+}
+
+
+encoding_struct! {
+    struct TeamVotes {
+        team: &PublicKey,
+        votes: u64,
+    }
+}
+
+
+encoding_struct! {
+    /// Votes of a fan in an election. Each vote counts `weight` times, see
+    /// `VotesSchema::delegated_votes`. `vote_hash` and `vote_height` point to
+    /// the last transaction which changed the ballot (both are zero if there is
+    /// no such transaction), `history_hash` is the root of the list of all such
+    /// transactions.
+    struct Ballot {
+        election_id: u64,
+        fan: &PublicKey,
+        choices: Vec<TeamVotes>,
+        weight: u64,
+        vote_hash: &Hash,
+        vote_height: u64,
+        history_len: u64,
+        history_hash: &Hash,
+    }
+}
+
+
+encoding_struct! {
+    /// Candidates ordered by the preference of a fan in a ranked election.
+    /// The ranking counts `weight` times, see `VotesSchema::delegated_votes`.
+    struct Ranking {
+        election_id: u64,
+        fan: &PublicKey,
+        teams: Vec<PublicKey>,
+        weight: u64,
+    }
+}
+
+
+encoding_struct! {
+    /// Votes of a candidate in an election. `voters_hash` is the root of the list
+    /// of vote changes which sum up to `votes`.
+    struct Tally {
+        election_id: u64,
+        team: &PublicKey,
+        votes: u64,
+        voters_len: u64,
+        voters_hash: &Hash,
+    }
+}
+
+
+encoding_struct! {
+    /// Change of a tally made by a fan. Negative `votes` are taken back
+    /// by a change or a revocation of the vote.
+    struct Voter {
+        fan: &PublicKey,
+        tx_hash: &Hash,
+        votes: i64,
+    }
+}
+
+
+encoding_struct! {
+    /// Change of a tally made by all transactions of the block at `height`
+    /// and the `total` votes of the team at the end of the block.
+    struct TallyDelta {
+        height: u64,
+        votes: i64,
+        total: u64,
+    }
+}
+
+
+impl Election {
+    pub fn has_candidate(&self, team: &PublicKey) -> bool {
+        self.candidates().contains(team)
+    }
+
+    /// Votes are accepted in blocks with height in `[start_height, end_height)`.
+    pub fn is_open(&self, height: u64) -> bool {
+        self.start_height() <= height && height < self.end_height()
+    }
+
+    pub fn is_secret(&self) -> bool {
+        self.reveal_height() != 0
+    }
+
+    pub fn mode(&self) -> BallotMode {
+        BallotMode::from_u8(self.ballot_mode()).expect("Ballot mode is checked on creation")
+    }
+}
+
+
+/// Commitment of a vote for the team: `hash(team || salt)`.
+pub fn vote_commitment(team: &PublicKey, salt: &Hash) -> Hash {
+    let mut buffer = Vec::with_capacity(team.as_ref().len() + salt.as_ref().len());
+    buffer.extend_from_slice(team.as_ref());
+    buffer.extend_from_slice(salt.as_ref());
+    crypto::hash(&buffer)
+}
+
+
+impl Commitment {
+    pub fn reveal(self) -> Self {
+        Self::new(self.election_id(), self.fan(), self.commitment(), true)
+    }
+}
+
+
+impl Ballot {
+    pub fn empty(election_id: u64, fan: &PublicKey) -> Self {
+        Self::new(election_id, fan, Vec::new(), 1, &Hash::zero(), 0, 0, &Hash::zero())
+    }
+
+    /// Total number of votes cast by the fan.
+    pub fn votes(&self) -> u64 {
+        self.choices().iter().map(|choice| choice.votes()).sum()
+    }
+
+    pub fn voted(&self) -> bool {
+        self.votes() > 0
+    }
+
+    /// The fan has voted in the election, but has taken all the votes back.
+    pub fn revoked(&self) -> bool {
+        self.history_len() > 0 && !self.voted()
+    }
+
+    pub fn votes_for(&self, team: &PublicKey) -> u64 {
+        self.choices()
+            .iter()
+            .find(|choice| choice.team() == team)
+            .map_or(0, |choice| choice.votes())
+    }
+
+    pub fn add_votes(self, team: &PublicKey, votes: u64) -> Self {
+        let mut choices = self.choices();
+        match choices.iter().position(|choice| choice.team() == team) {
+            Some(i) => {
+                let votes = choices[i].votes() + votes;
+                choices[i] = TeamVotes::new(team, votes);
+            }
+            None => choices.push(TeamVotes::new(team, votes)),
+        }
+        self.with_choices(choices)
+    }
+
+    /// Removes votes for the team; the fan must have cast at least `votes` votes for it.
+    pub fn remove_votes(self, team: &PublicKey, votes: u64) -> Self {
+        let choices = self.choices()
+            .into_iter()
+            .filter_map(|choice| {
+                if choice.team() != team {
+                    Some(choice)
+                } else if choice.votes() > votes {
+                    Some(TeamVotes::new(team, choice.votes() - votes))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        self.with_choices(choices)
+    }
+
+    /// Takes back all the votes of the fan.
+    pub fn clear(self) -> Self {
+        self.with_choices(Vec::new())
+    }
+
+    /// Records the transaction which changed the ballot in the block with the given height.
+    pub fn record(self, tx_hash: &Hash, height: u64, history_hash: &Hash) -> Self {
+        Self::new(
+            self.election_id(),
+            self.fan(),
+            self.choices(),
+            self.weight(),
+            tx_hash,
+            height,
+            self.history_len() + 1,
+            history_hash
+        )
+    }
+
+    pub fn with_weight(self, weight: u64) -> Self {
+        Self::new(
+            self.election_id(),
+            self.fan(),
+            self.choices(),
+            weight,
+            self.vote_hash(),
+            self.vote_height(),
+            self.history_len(),
+            self.history_hash()
+        )
+    }
+
+    fn with_choices(self, choices: Vec<TeamVotes>) -> Self {
+        Self::new(
+            self.election_id(),
+            self.fan(),
+            choices,
+            self.weight(),
+            self.vote_hash(),
+            self.vote_height(),
+            self.history_len(),
+            self.history_hash()
+        )
+    }
+}
+
+
+impl Tally {
+    pub fn empty(election_id: u64, team: &PublicKey) -> Self {
+        Self::new(election_id, team, 0, 0, &Hash::zero())
+    }
+
+    pub fn add_votes(self, votes: u64) -> Self {
+        Self::new(
+            self.election_id(),
+            self.team(),
+            self.votes().checked_add(votes).expect("Tally overflow is rejected by transactions"),
+            self.voters_len(),
+            self.voters_hash()
+        )
+    }
+
+    pub fn remove_votes(self, votes: u64) -> Self {
+        Self::new(
+            self.election_id(),
+            self.team(),
+            self.votes() - votes,
+            self.voters_len(),
+            self.voters_hash()
+        )
+    }
+
+    /// Records the change appended to the list of voters.
+    pub fn record(self, voters_hash: &Hash) -> Self {
+        Self::new(
+            self.election_id(),
+            self.team(),
+            self.votes(),
+            self.voters_len() + 1,
+            voters_hash
+        )
+    }
+}
@@ -1,20 +1,145 @@
 use exonum::blockchain::ExecutionError;
 
 
-#[derive(Debug, Fail)]
+/// Errors of the service transactions. The discriminant is the code stored in
+/// the `ExecutionError` of the rejected transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Fail)]
 #[repr(u8)]
 pub enum Error {
-    #[fail(display = "Wallet already exists")]
-    WalletAlreadyExists = 0,
+    #[fail(display = "Fan already exists")]
+    FanAlreadyExists = 0,
 
-    #[fail(display = "Sender doesn't exist")]
-    SenderNotFound = 1,
+    #[fail(display = "Fan doesn't exist")]
+    FanNotFound = 1,
 
-    #[fail(display = "Receiver doesn't exist")]
-    ReceiverNotFound = 2,
+    #[fail(display = "Team doesn't exist")]
+    TeamNotFound = 2,
 
-    #[fail(display = "Insufficient currency amount")]
-    InsufficientCurrencyAmount = 3,
+    #[fail(display = "Fan doesn't have enough votes left")]
+    VoteLimitExceeded = 3,
+
+    #[fail(display = "Election doesn't exist")]
+    ElectionNotFound = 4,
+
+    #[fail(display = "Team is not a candidate in the election")]
+    CandidateNotFound = 5,
+
+    #[fail(display = "Team is listed as a candidate more than once")]
+    DuplicateCandidate = 6,
+
+    #[fail(display = "Voting has not started yet")]
+    VotingNotStarted = 7,
+
+    #[fail(display = "Voting is already closed")]
+    VotingClosed = 8,
+
+    #[fail(display = "Voting window is empty")]
+    InvalidVotingWindow = 9,
+
+    #[fail(display = "Transaction author is not an administrator")]
+    Unauthorized = 10,
+
+    #[fail(display = "Fans must have at least one vote")]
+    InvalidMaxVotes = 11,
+
+    #[fail(display = "Fan hasn't voted for the team")]
+    VoteNotFound = 12,
+
+    #[fail(display = "Fan hasn't voted in the election")]
+    NotVoted = 13,
+
+    #[fail(display = "Vote is already revoked")]
+    VoteAlreadyRevoked = 14,
+
+    #[fail(display = "Unknown ballot mode")]
+    InvalidBallotMode = 15,
+
+    #[fail(display = "Election doesn't accept this kind of ballot")]
+    WrongBallotMode = 16,
+
+    #[fail(display = "Allocations must give votes to distinct teams")]
+    InvalidAllocation = 17,
+
+    #[fail(display = "Ranking must list distinct candidates")]
+    InvalidRanking = 18,
+
+    #[fail(display = "Commit phase is over")]
+    CommitPhaseOver = 19,
+
+    #[fail(display = "Reveal phase has not started yet")]
+    RevealNotStarted = 20,
+
+    #[fail(display = "Fan hasn't committed a vote")]
+    CommitmentNotFound = 21,
+
+    #[fail(display = "Vote is already revealed")]
+    VoteAlreadyRevealed = 22,
+
+    #[fail(display = "Team and salt don't match the commitment")]
+    CommitmentMismatch = 23,
+
+    #[fail(display = "Delegation would create a cycle")]
+    DelegationCycle = 24,
+
+    #[fail(display = "Fan hasn't delegated the vote")]
+    NotDelegated = 25,
+
+    /// Not returned anymore: fans who delegated may vote until a delegate
+    /// carries their vote. Kept to preserve the codes of other errors.
+    #[fail(display = "Fan has delegated the vote")]
+    VoteDelegated = 26,
+
+    #[fail(display = "Vote is already cast by the delegate")]
+    VotedByDelegate = 27,
+
+    #[fail(display = "Team already exists")]
+    TeamAlreadyExists = 28,
+
+    #[fail(display = "Fan has already cast all the votes")]
+    AlreadyVoted = 29,
+
+    #[fail(display = "Key is registered as a wallet of the other kind")]
+    KeyRegisteredAsOtherKind = 30,
+
+    #[fail(display = "Delegate doesn't exist")]
+    DelegateNotFound = 31,
+
+    #[fail(display = "Vote count overflows the tally")]
+    VoteCountOverflow = 32,
+
+    #[fail(display = "New team is the same as the old one")]
+    SameTeam = 33,
+}
+
+/// Every error of the service, indexed by code. Codes are the discriminants
+/// of the variants, so a new error is appended here with the next code.
+const ALL: [Error; 34] = [
+    Error::FanAlreadyExists, Error::FanNotFound, Error::TeamNotFound, Error::VoteLimitExceeded,
+    Error::ElectionNotFound, Error::CandidateNotFound, Error::DuplicateCandidate,
+    Error::VotingNotStarted, Error::VotingClosed, Error::InvalidVotingWindow, Error::Unauthorized,
+    Error::InvalidMaxVotes, Error::VoteNotFound, Error::NotVoted, Error::VoteAlreadyRevoked,
+    Error::InvalidBallotMode, Error::WrongBallotMode, Error::InvalidAllocation,
+    Error::InvalidRanking, Error::CommitPhaseOver, Error::RevealNotStarted,
+    Error::CommitmentNotFound, Error::VoteAlreadyRevealed, Error::CommitmentMismatch,
+    Error::DelegationCycle, Error::NotDelegated, Error::VoteDelegated, Error::VotedByDelegate,
+    Error::TeamAlreadyExists, Error::AlreadyVoted, Error::KeyRegisteredAsOtherKind,
+    Error::DelegateNotFound, Error::VoteCountOverflow, Error::SameTeam,
+];
+
+impl Error {
+    pub fn from_code(code: u8) -> Option<Self> {
+        ALL.get(code as usize).cloned()
+    }
+
+    /// Every error of the service in the order of codes.
+    pub fn all() -> Vec<Self> {
+        ALL.to_vec()
+    }
+
+    /// Name of the variant, e.g. `"AlreadyVoted"`.
+    pub fn name(&self) -> String {
+        format!("{:?}", self)
+    }
 }
 
 impl From<Error> for ExecutionError {
@@ -9,28 +9,111 @@ extern crate serde_json;
 
 
 pub mod constants;
+pub mod config;
 pub mod schema;
 pub mod api;
 pub mod wallet;
+pub mod election;
+pub mod tally;
 pub mod errors;
 pub mod transactions;
+pub mod client;
 
 
 pub mod service {
     use exonum::{api::Api,
                  blockchain::{ApiContext, Service, Transaction, TransactionSet},
-                 crypto::Hash,
+                 crypto::{Hash, PublicKey},
                  encoding,
                  messages::RawTransaction,
-                 storage::Snapshot};
+                 storage::{Fork, Snapshot}};
     use iron::Handler;
     use router::Router;
+    use serde_json::{self, Value};
 
     use constants::{SERVICE_NAME, SERVICE_ID};
     use api::VotesApi;
-    use transactions::Transactions;
+    use config::{BallotExport, ElectionExport, FanConfig, VotesConfig};
+    use election::{Ballot, BallotMode, Commitment, Election, Ranking};
+    use schema::{election_key, VotesSchema};
+    use transactions::{validate_election, Transactions};
+    use wallet::{FanWallet, TeamWallet};
 
-    pub struct VotesService;
+    pub struct VotesService {
+        config: VotesConfig,
+    }
+
+    impl VotesService {
+        /// Creates the service with the configuration which is written
+        /// to the blockchain in the genesis block.
+        pub fn new(config: VotesConfig) -> Self {
+            VotesService { config }
+        }
+    }
+
+    /// Registers the fans and restores their delegations.
+    fn import_fans(schema: &mut VotesSchema<&mut Fork>, fans: &[FanConfig]) {
+        for fan in fans {
+            assert!(schema.team_wallet(&fan.pub_key).is_none(), "Fan key is registered as a team");
+            schema.fan_wallets_mut().put(&fan.pub_key, FanWallet::create(&fan.pub_key, &fan.name));
+        }
+        for fan in fans {
+            if let Some(ref delegate) = fan.delegate {
+                assert!(schema.fan_wallet(delegate).is_some(), "Delegate of the fan doesn't exist");
+                assert!(!schema.delegation_chain(delegate).contains(&fan.pub_key),
+                        "Delegation would create a cycle");
+                schema.set_delegate(&fan.pub_key, delegate);
+            }
+        }
+    }
+
+    /// Adds the election and counts the exported votes in it. Votes get no
+    /// transaction hashes, so they are absent from ballot histories and the votes log.
+    fn import_election(schema: &mut VotesSchema<&mut Fork>, config: &ElectionExport) {
+        let election = Election::new(schema.next_election_id(), &config.title,
+                                     config.candidates.clone(), config.start_height,
+                                     config.end_height, config.max_votes,
+                                     config.ballot_mode as u8, config.reveal_height, 0);
+        if let Err(error) = validate_election(schema, &election) {
+            panic!("Invalid imported election: {}", error);
+        }
+        schema.add_election(election.clone());
+        for ballot in &config.ballots {
+            import_ballot(schema, &election, ballot);
+        }
+    }
+
+    fn import_ballot(schema: &mut VotesSchema<&mut Fork>, election: &Election,
+                     config: &BallotExport) {
+        let (election_id, fan) = (election.id(), &config.fan);
+        assert!(schema.fan_wallet(fan).is_some(), "Ballot of an unknown fan");
+        let teams = config.choices.iter().map(|choice| &choice.0).chain(&config.ranking);
+        for team in teams {
+            assert!(election.has_candidate(team), "Vote for a team which is not a candidate");
+        }
+        schema.add_represented_fans(election_id, fan, &config.delegators);
+        if let Some(ref commitment) = config.commitment {
+            let revealed = !config.choices.is_empty();
+            schema.save_commitment(Commitment::new(election_id, fan, commitment, revealed));
+        }
+        if election.mode() == BallotMode::Ranked {
+            if let Some(first) = config.ranking.first() {
+                schema.add_tally_votes(election_id, first, fan, &Hash::zero(), config.weight, 0);
+                let ranking = Ranking::new(election_id, fan, config.ranking.clone(), config.weight);
+                schema.put_ranking(ranking);
+            }
+        } else if !config.choices.is_empty() {
+            let mut ballot = Ballot::empty(election_id, fan).with_weight(config.weight);
+            for &(ref team, votes) in &config.choices {
+                let tally_votes = votes.checked_mul(config.weight)
+                    .expect("Imported votes overflow");
+                schema.add_tally_votes(election_id, team, fan, &Hash::zero(), tally_votes, 0);
+                ballot = ballot.add_votes(team, votes);
+            }
+            assert!(ballot.votes() <= election.max_votes(), "Ballot exceeds the vote limit");
+            schema.ballots_mut().put(&election_key(election_id, fan), ballot);
+        }
+    }
 
     impl Service for VotesService {
         fn service_name(&self) -> &'static str { SERVICE_NAME }
@@ -42,8 +125,38 @@ pub mod service {
             Ok(tx.into())
         }
 
-        fn state_hash(&self, _: &Snapshot) -> Vec<Hash> {
-            vec![]
+        fn state_hash(&self, snapshot: &Snapshot) -> Vec<Hash> {
+            VotesSchema::new(snapshot).state_hash()
+        }
+
+        fn initialize(&self, fork: &mut Fork) -> Value {
+            let mut schema = VotesSchema::new(fork);
+            for admin in &self.config.admins {
+                schema.admins_mut().push(*admin);
+            }
+            for team in &self.config.teams {
+                let wallet = TeamWallet::new(&team.pub_key, &team.name);
+                schema.team_wallets_mut().put(&team.pub_key, wallet);
+            }
+            if let Some(ref election) = self.config.election {
+                let candidates: Vec<PublicKey> = self.config.teams
+                    .iter()
+                    .map(|team| team.pub_key)
+                    .collect();
+                let election = Election::new(schema.next_election_id(), &election.title, candidates,
+                                             election.start_height, election.end_height,
+                                             election.max_votes_per_fan, election.ballot_mode as u8,
+                                             election.reveal_height, 0);
+                if let Err(error) = validate_election(&schema, &election) {
+                    panic!("Invalid genesis election: {}", error);
+                }
+                schema.add_election(election);
+            }
+            import_fans(&mut schema, &self.config.fans);
+            for election in &self.config.elections {
+                import_election(&mut schema, election);
+            }
+            serde_json::to_value(&self.config).unwrap()
         }
 
         fn public_api_handler(&self, ctx: &ApiContext) -> Option<Box<Handler>> {
@@ -1,9 +1,45 @@
 use constants::SERVICE_NAME;
-use exonum::{crypto::PublicKey,
-             storage::{Fork, ProofMapIndex, Snapshot}};
+use exonum::{crypto::{self, Hash, PublicKey},
+             storage::{Fork, KeySetIndex, MapIndex, ProofListIndex, ProofMapIndex, Snapshot,
+                       StorageKey}};
+use election::*;
 use wallet::*;
 
 
+/// Key of a per-election entry (ballot of a fan or tally of a team).
+pub fn election_key(election_id: u64, pub_key: &PublicKey) -> Hash {
+    let mut buffer = vec![0; election_id.size() + pub_key.size()];
+    election_id.write(&mut buffer[..8]);
+    pub_key.write(&mut buffer[8..]);
+    crypto::hash(&buffer)
+}
+
+
+/// Key of a team in the leaderboard of an election. Keys are ordered by
+/// the number of votes descending, teams with equal votes by public key.
+pub fn leaderboard_key(votes: u64, team: &PublicKey) -> Vec<u8> {
+    let mut buffer = vec![0; votes.size() + team.size()];
+    (u64::max_value() - votes).write(&mut buffer[..8]);
+    team.write(&mut buffer[8..]);
+    buffer
+}
+
+
+/// Prefix of the leaderboard keys of the teams with the number of votes,
+/// which is also the first key of such teams.
+fn leaderboard_prefix(votes: u64) -> Vec<u8> {
+    let mut buffer = vec![0; votes.size()];
+    (u64::max_value() - votes).write(&mut buffer);
+    buffer
+}
+
+
+/// Number of votes and the team encoded in the leaderboard key.
+pub fn parse_leaderboard_key(key: &[u8]) -> (u64, PublicKey) {
+    (u64::max_value() - u64::read(&key[..8]), PublicKey::read(&key[8..]))
+}
+
+
 pub struct VotesSchema<T> {
     view: T,
 }
@@ -23,6 +59,24 @@ impl<T: AsRef<Snapshot>> VotesSchema<T> {
         self.fan_wallets().get(pub_key)
     }
 
+    /// Fans who delegated their votes directly to the fan.
+    pub fn delegators(&self, fan: &PublicKey) -> KeySetIndex<&Snapshot, PublicKey> {
+        KeySetIndex::new_in_family(format!("{}.{}", SERVICE_NAME, "delegators"),
+                                   fan,
+                                   self.view.as_ref())
+    }
+
+    /// The fan followed by its delegate, the delegate of the delegate and so on.
+    pub fn delegation_chain(&self, fan: &PublicKey) -> Vec<PublicKey> {
+        let mut chain = Vec::new();
+        let mut next = self.fan_wallet(fan);
+        while let Some(wallet) = next {
+            chain.push(*wallet.pub_key());
+            next = if wallet.has_delegate() { self.fan_wallet(wallet.delegate()) } else { None };
+        }
+        chain
+    }
+
     pub fn team_wallets(&self) -> ProofMapIndex<&Snapshot, PublicKey, TeamWallet> {
         ProofMapIndex::new(format!("{}.{}", SERVICE_NAME, "team_wallets"),
                            self.view.as_ref())
@@ -31,6 +85,289 @@ impl<T: AsRef<Snapshot>> VotesSchema<T> {
     pub fn team_wallet(&self, pub_key: &PublicKey) -> Option<TeamWallet> {
         self.team_wallets().get(pub_key)
     }
+
+    pub fn admins(&self) -> ProofListIndex<&Snapshot, PublicKey> {
+        ProofListIndex::new(format!("{}.{}", SERVICE_NAME, "admins"),
+                            self.view.as_ref())
+    }
+
+    pub fn is_admin(&self, pub_key: &PublicKey) -> bool {
+        self.admins().iter().any(|admin| admin == *pub_key)
+    }
+
+    pub fn elections(&self) -> ProofListIndex<&Snapshot, Election> {
+        ProofListIndex::new(format!("{}.{}", SERVICE_NAME, "elections"),
+                            self.view.as_ref())
+    }
+
+    pub fn election(&self, election_id: u64) -> Option<Election> {
+        self.elections().get(election_id)
+    }
+
+    pub fn ballots(&self) -> ProofMapIndex<&Snapshot, Hash, Ballot> {
+        ProofMapIndex::new(format!("{}.{}", SERVICE_NAME, "ballots"),
+                           self.view.as_ref())
+    }
+
+    pub fn ballot(&self, election_id: u64, fan: &PublicKey) -> Option<Ballot> {
+        self.ballots().get(&election_key(election_id, fan))
+    }
+
+    /// Transactions which changed the ballot, oldest first.
+    pub fn ballot_history(&self, election_id: u64, fan: &PublicKey) -> ProofListIndex<&Snapshot, Hash> {
+        ProofListIndex::new_in_family(format!("{}.{}", SERVICE_NAME, "ballot_history"),
+                                      &election_key(election_id, fan),
+                                      self.view.as_ref())
+    }
+
+    /// Fans whose votes in the election were cast by their delegates.
+    pub fn represented_fans(&self, election_id: u64) -> KeySetIndex<&Snapshot, PublicKey> {
+        KeySetIndex::new_in_family(format!("{}.{}", SERVICE_NAME, "represented_fans"),
+                                   &election_id,
+                                   self.view.as_ref())
+    }
+
+    /// Fans whose votes are carried by the current ballot of the delegate.
+    pub fn ballot_delegators(&self, election_id: u64, delegate: &PublicKey)
+                             -> KeySetIndex<&Snapshot, PublicKey> {
+        KeySetIndex::new_in_family(format!("{}.{}", SERVICE_NAME, "ballot_delegators"),
+                                   &election_key(election_id, delegate),
+                                   self.view.as_ref())
+    }
+
+    /// Fans whose votes in the election go with the first vote of the fan: those
+    /// who delegated to it directly or through other fans and haven't voted
+    /// (or committed a secret vote) in the election themselves.
+    pub fn delegated_votes(&self, election_id: u64, fan: &PublicKey) -> Vec<PublicKey> {
+        let represented = self.represented_fans(election_id);
+        let mut votes = Vec::new();
+        let mut delegates = vec![*fan];
+        while let Some(delegate) = delegates.pop() {
+            for delegator in self.delegators(&delegate).iter() {
+                let voted = self.ballot(election_id, &delegator).map_or(false, |ballot| ballot.voted())
+                    || self.ranking(election_id, &delegator).is_some()
+                    || self.commitment(election_id, &delegator).is_some();
+                if !voted && !represented.contains(&delegator) {
+                    votes.push(delegator);
+                    delegates.push(delegator);
+                }
+            }
+        }
+        votes
+    }
+
+    pub fn tallies(&self) -> ProofMapIndex<&Snapshot, Hash, Tally> {
+        ProofMapIndex::new(format!("{}.{}", SERVICE_NAME, "tallies"),
+                           self.view.as_ref())
+    }
+
+    pub fn tally(&self, election_id: u64, team: &PublicKey) -> Option<Tally> {
+        self.tallies().get(&election_key(election_id, team))
+    }
+
+    pub fn rankings(&self) -> ProofMapIndex<&Snapshot, Hash, Ranking> {
+        ProofMapIndex::new(format!("{}.{}", SERVICE_NAME, "rankings"),
+                           self.view.as_ref())
+    }
+
+    pub fn ranking(&self, election_id: u64, fan: &PublicKey) -> Option<Ranking> {
+        self.rankings().get(&election_key(election_id, fan))
+    }
+
+    /// Fans who submitted rankings in the election.
+    pub fn ranked_fans(&self, election_id: u64) -> KeySetIndex<&Snapshot, PublicKey> {
+        KeySetIndex::new_in_family(format!("{}.{}", SERVICE_NAME, "ranked_fans"),
+                                   &election_id,
+                                   self.view.as_ref())
+    }
+
+    /// Rankings of all fans in the election with their weights.
+    pub fn election_rankings(&self, election_id: u64) -> Vec<(Vec<PublicKey>, u64)> {
+        self.ranked_fans(election_id)
+            .iter()
+            .filter_map(|fan| self.ranking(election_id, &fan))
+            .map(|ranking| (ranking.teams(), ranking.weight()))
+            .collect()
+    }
+
+    pub fn commitments(&self) -> ProofMapIndex<&Snapshot, Hash, Commitment> {
+        ProofMapIndex::new(format!("{}.{}", SERVICE_NAME, "commitments"),
+                           self.view.as_ref())
+    }
+
+    pub fn commitment(&self, election_id: u64, fan: &PublicKey) -> Option<Commitment> {
+        self.commitments().get(&election_key(election_id, fan))
+    }
+
+    /// Fans who committed votes in the election.
+    pub fn committed_fans(&self, election_id: u64) -> KeySetIndex<&Snapshot, PublicKey> {
+        KeySetIndex::new_in_family(format!("{}.{}", SERVICE_NAME, "committed_fans"),
+                                   &election_id,
+                                   self.view.as_ref())
+    }
+
+    /// Fans whose committed votes are not revealed and therefore not counted.
+    pub fn unrevealed_fans(&self, election_id: u64) -> Vec<PublicKey> {
+        self.committed_fans(election_id)
+            .iter()
+            .filter(|fan| {
+                self.commitment(election_id, fan).map_or(false, |commitment| !commitment.revealed())
+            })
+            .collect()
+    }
+
+    /// Hashes of all accepted transactions which changed ballots, in the order of execution.
+    pub fn votes_log(&self) -> ProofListIndex<&Snapshot, Hash> {
+        ProofListIndex::new(format!("{}.{}", SERVICE_NAME, "votes_log"),
+                            self.view.as_ref())
+    }
+
+    /// Changes of the tally made by fans, oldest first.
+    pub fn voters(&self, election_id: u64, team: &PublicKey) -> ProofListIndex<&Snapshot, Voter> {
+        ProofListIndex::new_in_family(format!("{}.{}", SERVICE_NAME, "voters"),
+                                      &election_key(election_id, team),
+                                      self.view.as_ref())
+    }
+
+    /// Candidates of the election ordered by the number of votes.
+    /// See `leaderboard_key`.
+    pub fn leaderboard(&self, election_id: u64) -> KeySetIndex<&Snapshot, Vec<u8>> {
+        KeySetIndex::new_in_family(format!("{}.{}", SERVICE_NAME, "leaderboard"),
+                                   &election_id,
+                                   self.view.as_ref())
+    }
+
+    /// Number of the candidates of the election with each number of votes,
+    /// keyed by `u64::max_value() - votes` to iterate from the most voted ones.
+    pub fn vote_counts(&self, election_id: u64) -> MapIndex<&Snapshot, u64, u64> {
+        MapIndex::new_in_family(format!("{}.{}", SERVICE_NAME, "vote_counts"),
+                                &election_id,
+                                self.view.as_ref())
+    }
+
+    /// Tallies of the candidates of the election with at least `min_votes` and
+    /// at most `max_votes` votes, most voted first, skipping `from` first teams
+    /// and returning at most `limit` of them. Teams with equal votes are skipped
+    /// by their counts in `vote_counts`, so only the teams tied at the first
+    /// returned place are iterated over.
+    pub fn rating(&self, election_id: u64, min_votes: u64, max_votes: u64,
+                  from: u64, limit: u64) -> Option<Vec<Tally>> {
+        self.election(election_id)?;
+        let mut skip = from;
+        let mut start = None;
+        let counts = self.vote_counts(election_id);
+        for (key, count) in counts.iter_from(&(u64::max_value() - max_votes)) {
+            let votes = u64::max_value() - key;
+            if skip < count || votes < min_votes {
+                start = Some(votes);
+                break;
+            }
+            skip -= count;
+        }
+        let start = match start {
+            Some(votes) => votes,
+            None => return Some(Vec::new()),
+        };
+        let tallies = self.leaderboard(election_id)
+            .iter_from(&leaderboard_prefix(start))
+            .map(|key| parse_leaderboard_key(&key))
+            .take_while(|&(votes, _)| votes >= min_votes)
+            .skip(skip as usize)
+            .take(limit as usize)
+            .filter_map(|(_, team)| self.tally(election_id, &team))
+            .collect();
+        Some(tallies)
+    }
+
+    /// Position of the candidate in the leaderboard, starting from zero: the number
+    /// of candidates with more votes, summed over `vote_counts`, plus the number
+    /// of candidates with equal votes and smaller keys.
+    pub fn team_rank(&self, election_id: u64, team: &PublicKey) -> Option<u64> {
+        let tally = self.tally(election_id, team)?;
+        let bucket = u64::max_value() - tally.votes();
+        let above: u64 = self.vote_counts(election_id)
+            .iter()
+            .take_while(|&(key, _)| key < bucket)
+            .map(|(_, count)| count)
+            .sum();
+        let key = leaderboard_key(tally.votes(), team);
+        let tied = self.leaderboard(election_id)
+            .iter_from(&leaderboard_prefix(tally.votes()))
+            .take_while(|other| *other < key)
+            .count();
+        Some(above + tied as u64)
+    }
+
+    /// Changes of the tally made by blocks, oldest first.
+    pub fn tally_deltas(&self, election_id: u64, team: &PublicKey)
+                        -> ProofListIndex<&Snapshot, TallyDelta> {
+        ProofListIndex::new_in_family(format!("{}.{}", SERVICE_NAME, "tally_deltas"),
+                                      &election_key(election_id, team),
+                                      self.view.as_ref())
+    }
+
+    /// Votes of the team at the end of each block which changed its tally.
+    pub fn team_history(&self, election_id: u64, team: &PublicKey) -> Vec<(u64, u64)> {
+        self.tally_deltas(election_id, team)
+            .iter()
+            .map(|delta| (delta.height(), delta.total()))
+            .collect()
+    }
+
+    /// Votes of the team at the end of the block at the height, found by a binary
+    /// search for the last change of the tally at or below the height.
+    pub fn tally_at(&self, election_id: u64, team: &PublicKey, height: u64) -> u64 {
+        let deltas = self.tally_deltas(election_id, team);
+        let (mut low, mut high) = (0, deltas.len());
+        while low < high {
+            let middle = (low + high) / 2;
+            if deltas.get(middle).unwrap().height() <= height {
+                low = middle + 1;
+            } else {
+                high = middle;
+            }
+        }
+        if low == 0 { 0 } else { deltas.get(low - 1).unwrap().total() }
+    }
+
+    /// Merkle roots of the `tally_deltas` lists, which anchor them in the state
+    /// hash. The root for a team is stored under `election_key`.
+    pub fn history_roots(&self) -> ProofMapIndex<&Snapshot, Hash, Hash> {
+        ProofMapIndex::new(format!("{}.{}", SERVICE_NAME, "history_roots"),
+                           self.view.as_ref())
+    }
+
+    /// Tallies of the candidates at the end of the block at the height, ordered
+    /// as in the leaderboard, or `None` if the election didn't exist at the height.
+    pub fn rating_at(&self, election_id: u64, height: u64) -> Option<Vec<TeamVotes>> {
+        let election = self.election(election_id)?;
+        if height < election.created_height() {
+            return None;
+        }
+        let mut rating: Vec<(u64, PublicKey)> = election.candidates()
+            .iter()
+            .map(|team| (self.tally_at(election_id, team, height), *team))
+            .collect();
+        rating.sort_by_key(|&(votes, ref team)| leaderboard_key(votes, team));
+        Some(rating.iter().map(|&(votes, ref team)| TeamVotes::new(team, votes)).collect())
+    }
+
+    /// Merkle roots of all proof indexes of the service, in the order
+    /// they are committed to the block state hash.
+    pub fn state_hash(&self) -> Vec<Hash> {
+        vec![
+            self.fan_wallets().merkle_root(),
+            self.team_wallets().merkle_root(),
+            self.elections().merkle_root(),
+            self.ballots().merkle_root(),
+            self.tallies().merkle_root(),
+            self.admins().merkle_root(),
+            self.votes_log().merkle_root(),
+            self.rankings().merkle_root(),
+            self.commitments().merkle_root(),
+            self.history_roots().merkle_root(),
+        ]
+    }
 }
 
 
@@ -41,9 +378,263 @@ impl<'a> VotesSchema<&'a mut Fork> {
                            &mut self.view)
     }
 
+    pub fn delegators_mut(&mut self, fan: &PublicKey) -> KeySetIndex<&mut Fork, PublicKey> {
+        KeySetIndex::new_in_family(format!("{}.{}", SERVICE_NAME, "delegators"),
+                                   fan,
+                                   &mut self.view)
+    }
+
+    /// Moves the vote of the fan to the delegate (or back to the fan itself)
+    /// and updates the weights of the old and the new delegation chains.
+    pub fn set_delegate(&mut self, fan: &PublicKey, delegate: &PublicKey) {
+        let wallet = self.fan_wallet(fan).expect("Wallet of the delegating fan");
+        if wallet.has_delegate() {
+            self.delegators_mut(wallet.delegate()).remove(fan);
+            for key in self.delegation_chain(wallet.delegate()) {
+                let chain_wallet = self.fan_wallet(&key).unwrap().remove_weight(wallet.weight());
+                self.fan_wallets_mut().put(&key, chain_wallet);
+            }
+        }
+        if delegate != fan {
+            self.delegators_mut(delegate).insert(*fan);
+            for key in self.delegation_chain(delegate) {
+                let chain_wallet = self.fan_wallet(&key).unwrap().add_weight(wallet.weight());
+                self.fan_wallets_mut().put(&key, chain_wallet);
+            }
+        }
+        self.fan_wallets_mut().put(fan, wallet.set_delegate(delegate));
+    }
+
     pub fn team_wallets_mut(&mut self)
                            -> ProofMapIndex<&mut Fork, PublicKey, TeamWallet> {
         ProofMapIndex::new(format!("{}.{}", SERVICE_NAME, "team_wallets"),
                            &mut self.view)
     }
+
+    pub fn admins_mut(&mut self) -> ProofListIndex<&mut Fork, PublicKey> {
+        ProofListIndex::new(format!("{}.{}", SERVICE_NAME, "admins"),
+                            &mut self.view)
+    }
+
+    pub fn elections_mut(&mut self) -> ProofListIndex<&mut Fork, Election> {
+        ProofListIndex::new(format!("{}.{}", SERVICE_NAME, "elections"),
+                            &mut self.view)
+    }
+
+    /// Id of the next added election.
+    pub fn next_election_id(&self) -> u64 {
+        self.elections().len()
+    }
+
+    /// Adds an election with zero tallies for all candidates and returns its id.
+    /// The election must have id returned by `next_election_id`.
+    pub fn add_election(&mut self, election: Election) -> u64 {
+        let election_id = election.id();
+        assert_eq!(election_id, self.next_election_id());
+        for team in &election.candidates() {
+            self.put_tally(Tally::empty(election_id, team));
+        }
+        self.elections_mut().push(election);
+        election_id
+    }
+
+    pub fn ballots_mut(&mut self) -> ProofMapIndex<&mut Fork, Hash, Ballot> {
+        ProofMapIndex::new(format!("{}.{}", SERVICE_NAME, "ballots"),
+                           &mut self.view)
+    }
+
+    pub fn ballot_history_mut(&mut self, election_id: u64, fan: &PublicKey)
+                              -> ProofListIndex<&mut Fork, Hash> {
+        ProofListIndex::new_in_family(format!("{}.{}", SERVICE_NAME, "ballot_history"),
+                                      &election_key(election_id, fan),
+                                      &mut self.view)
+    }
+
+    pub fn represented_fans_mut(&mut self, election_id: u64) -> KeySetIndex<&mut Fork, PublicKey> {
+        KeySetIndex::new_in_family(format!("{}.{}", SERVICE_NAME, "represented_fans"),
+                                   &election_id,
+                                   &mut self.view)
+    }
+
+    pub fn ballot_delegators_mut(&mut self, election_id: u64, delegate: &PublicKey)
+                                 -> KeySetIndex<&mut Fork, PublicKey> {
+        KeySetIndex::new_in_family(format!("{}.{}", SERVICE_NAME, "ballot_delegators"),
+                                   &election_key(election_id, delegate),
+                                   &mut self.view)
+    }
+
+    /// Marks the fans as represented by the ballot of the delegate.
+    pub fn add_represented_fans(&mut self, election_id: u64, delegate: &PublicKey,
+                                fans: &[PublicKey]) {
+        for fan in fans {
+            self.represented_fans_mut(election_id).insert(*fan);
+            self.ballot_delegators_mut(election_id, delegate).insert(*fan);
+        }
+    }
+
+    /// Releases the fans represented by the ballot of the delegate, so that they
+    /// can vote themselves or be represented by the next ballot of the delegate.
+    pub fn release_represented_fans(&mut self, election_id: u64, delegate: &PublicKey) {
+        let fans: Vec<PublicKey> = self.ballot_delegators(election_id, delegate).iter().collect();
+        for fan in &fans {
+            self.represented_fans_mut(election_id).remove(fan);
+        }
+        self.ballot_delegators_mut(election_id, delegate).clear();
+    }
+
+    pub fn tallies_mut(&mut self) -> ProofMapIndex<&mut Fork, Hash, Tally> {
+        ProofMapIndex::new(format!("{}.{}", SERVICE_NAME, "tallies"),
+                           &mut self.view)
+    }
+
+    pub fn rankings_mut(&mut self) -> ProofMapIndex<&mut Fork, Hash, Ranking> {
+        ProofMapIndex::new(format!("{}.{}", SERVICE_NAME, "rankings"),
+                           &mut self.view)
+    }
+
+    pub fn ranked_fans_mut(&mut self, election_id: u64) -> KeySetIndex<&mut Fork, PublicKey> {
+        KeySetIndex::new_in_family(format!("{}.{}", SERVICE_NAME, "ranked_fans"),
+                                   &election_id,
+                                   &mut self.view)
+    }
+
+    /// Stores the ranking of the fan, replacing the previous one.
+    pub fn save_ranking(&mut self, ranking: Ranking, tx_hash: &Hash) {
+        self.votes_log_mut().push(*tx_hash);
+        self.put_ranking(ranking);
+    }
+
+    /// Stores the ranking without recording a transaction, e.g. on import.
+    pub fn put_ranking(&mut self, ranking: Ranking) {
+        self.ranked_fans_mut(ranking.election_id()).insert(*ranking.fan());
+        self.rankings_mut().put(&election_key(ranking.election_id(), ranking.fan()), ranking);
+    }
+
+    pub fn commitments_mut(&mut self) -> ProofMapIndex<&mut Fork, Hash, Commitment> {
+        ProofMapIndex::new(format!("{}.{}", SERVICE_NAME, "commitments"),
+                           &mut self.view)
+    }
+
+    pub fn committed_fans_mut(&mut self, election_id: u64) -> KeySetIndex<&mut Fork, PublicKey> {
+        KeySetIndex::new_in_family(format!("{}.{}", SERVICE_NAME, "committed_fans"),
+                                   &election_id,
+                                   &mut self.view)
+    }
+
+    /// Stores the commitment of the fan, replacing the previous one.
+    pub fn save_commitment(&mut self, commitment: Commitment) {
+        self.committed_fans_mut(commitment.election_id()).insert(*commitment.fan());
+        self.commitments_mut().put(&election_key(commitment.election_id(), commitment.fan()),
+                                   commitment);
+    }
+
+    pub fn votes_log_mut(&mut self) -> ProofListIndex<&mut Fork, Hash> {
+        ProofListIndex::new(format!("{}.{}", SERVICE_NAME, "votes_log"),
+                            &mut self.view)
+    }
+
+    /// Appends the transaction to the ballot history and the votes log
+    /// and stores the updated ballot.
+    pub fn save_ballot(&mut self, ballot: Ballot, tx_hash: &Hash, height: u64) {
+        let history_hash = {
+            let mut history = self.ballot_history_mut(ballot.election_id(), ballot.fan());
+            history.push(*tx_hash);
+            history.merkle_root()
+        };
+        self.votes_log_mut().push(*tx_hash);
+        let ballot = ballot.record(tx_hash, height, &history_hash);
+        self.ballots_mut().put(&election_key(ballot.election_id(), ballot.fan()), ballot);
+    }
+
+    pub fn voters_mut(&mut self, election_id: u64, team: &PublicKey)
+                      -> ProofListIndex<&mut Fork, Voter> {
+        ProofListIndex::new_in_family(format!("{}.{}", SERVICE_NAME, "voters"),
+                                      &election_key(election_id, team),
+                                      &mut self.view)
+    }
+
+    pub fn add_tally_votes(&mut self, election_id: u64, team: &PublicKey,
+                           fan: &PublicKey, tx_hash: &Hash, votes: u64, height: u64) {
+        let voters_hash = self.push_voter(election_id, team, Voter::new(fan, tx_hash, votes as i64));
+        let tally = self.tally(election_id, team)
+            .unwrap_or_else(|| Tally::empty(election_id, team))
+            .add_votes(votes)
+            .record(&voters_hash);
+        let total = tally.votes();
+        self.put_tally(tally);
+        self.save_delta(election_id, team, height, votes as i64, total);
+    }
+
+    pub fn remove_tally_votes(&mut self, election_id: u64, team: &PublicKey,
+                              fan: &PublicKey, tx_hash: &Hash, votes: u64, height: u64) {
+        let voters_hash = self.push_voter(election_id, team, Voter::new(fan, tx_hash, -(votes as i64)));
+        let tally = self.tally(election_id, team)
+            .expect("Tally of a voted candidate")
+            .remove_votes(votes)
+            .record(&voters_hash);
+        let total = tally.votes();
+        self.put_tally(tally);
+        self.save_delta(election_id, team, height, -(votes as i64), total);
+    }
+
+    pub fn leaderboard_mut(&mut self, election_id: u64) -> KeySetIndex<&mut Fork, Vec<u8>> {
+        KeySetIndex::new_in_family(format!("{}.{}", SERVICE_NAME, "leaderboard"),
+                                   &election_id,
+                                   &mut self.view)
+    }
+
+    pub fn vote_counts_mut(&mut self, election_id: u64) -> MapIndex<&mut Fork, u64, u64> {
+        MapIndex::new_in_family(format!("{}.{}", SERVICE_NAME, "vote_counts"),
+                                &election_id,
+                                &mut self.view)
+    }
+
+    /// Stores the tally and moves the team to its new place in the leaderboard.
+    fn put_tally(&mut self, tally: Tally) {
+        let election_id = tally.election_id();
+        if let Some(old) = self.tally(election_id, tally.team()) {
+            self.leaderboard_mut(election_id).remove(&leaderboard_key(old.votes(), old.team()));
+            let bucket = u64::max_value() - old.votes();
+            let mut counts = self.vote_counts_mut(election_id);
+            match counts.get(&bucket) {
+                Some(count) if count > 1 => counts.put(&bucket, count - 1),
+                _ => counts.remove(&bucket),
+            }
+        }
+        self.leaderboard_mut(election_id).insert(leaderboard_key(tally.votes(), tally.team()));
+        let bucket = u64::max_value() - tally.votes();
+        let count = self.vote_counts(election_id).get(&bucket).unwrap_or(0);
+        self.vote_counts_mut(election_id).put(&bucket, count + 1);
+        self.tallies_mut().put(&election_key(election_id, tally.team()), tally);
+    }
+
+    pub fn tally_deltas_mut(&mut self, election_id: u64, team: &PublicKey)
+                            -> ProofListIndex<&mut Fork, TallyDelta> {
+        ProofListIndex::new_in_family(format!("{}.{}", SERVICE_NAME, "tally_deltas"),
+                                      &election_key(election_id, team),
+                                      &mut self.view)
+    }
+
+    /// Adds the change of the tally to the delta of the block at the height.
+    fn save_delta(&mut self, election_id: u64, team: &PublicKey, height: u64, votes: i64,
+                  total: u64) {
+        let root = {
+            let mut deltas = self.tally_deltas_mut(election_id, team);
+            match deltas.last() {
+                Some(ref last) if last.height() == height => {
+                    let index = deltas.len() - 1;
+                    deltas.set(index, TallyDelta::new(height, last.votes() + votes, total));
+                }
+                _ => deltas.push(TallyDelta::new(height, votes, total)),
+            }
+            deltas.merkle_root()
+        };
+        self.history_roots_mut().put(&election_key(election_id, team), root);
+    }
+
+    fn push_voter(&mut self, election_id: u64, team: &PublicKey, voter: Voter) -> Hash {
+        let mut voters = self.voters_mut(election_id, team);
+        voters.push(voter);
+        voters.merkle_root()
+    }
 }
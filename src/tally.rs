@@ -0,0 +1,100 @@
+//! Results of ranked elections computed from the stored rankings of fans.
+
+use std::str::FromStr;
+
+use exonum::crypto::PublicKey;
+
+
+/// How the rating of an election is computed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TallyMethod {
+    /// Number of votes (first preferences in ranked elections) of each team.
+    Plurality,
+    /// Each ranking gives `n - 1 - i` points to the team at position `i`,
+    /// where `n` is the number of candidates.
+    Borda,
+    /// Teams with the fewest first preferences are eliminated one by one
+    /// until some team has the majority of the remaining rankings.
+    InstantRunoff,
+}
+
+
+impl FromStr for TallyMethod {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "plurality" => Ok(TallyMethod::Plurality),
+            "borda" => Ok(TallyMethod::Borda),
+            "irv" => Ok(TallyMethod::InstantRunoff),
+            _ => Err(()),
+        }
+    }
+}
+
+
+/// Borda count of the candidates, most points first. Each ranking counts
+/// as many times as its weight.
+pub fn borda(candidates: &[PublicKey], rankings: &[(Vec<PublicKey>, u64)])
+             -> Vec<(PublicKey, u64)> {
+    let n = candidates.len() as u64;
+    let mut points: Vec<(PublicKey, u64)> = candidates.iter().map(|team| (*team, 0)).collect();
+    for &(ref ranking, weight) in rankings {
+        for (i, team) in ranking.iter().enumerate() {
+            if let Some(entry) = points.iter_mut().find(|entry| entry.0 == *team) {
+                entry.1 += n.saturating_sub(1 + i as u64) * weight;
+            }
+        }
+    }
+    points.sort_by(|l, r| r.1.cmp(&l.1));
+    points
+}
+
+
+/// Instant-runoff result. The winner goes first, the other candidates follow
+/// in reverse order of elimination, each with the number of rankings it had
+/// in the last round it took part in.
+///
+/// Each ranking counts as many times as its weight. Ties for the last place
+/// are broken by eliminating the candidate listed later in the election,
+/// so the result is the same on every node.
+pub fn instant_runoff(candidates: &[PublicKey], rankings: &[(Vec<PublicKey>, u64)])
+                      -> Vec<(PublicKey, u64)> {
+    let mut remaining: Vec<PublicKey> = candidates.to_vec();
+    let mut eliminated: Vec<(PublicKey, u64)> = Vec::new();
+    while !remaining.is_empty() {
+        let mut counts: Vec<u64> = vec![0; remaining.len()];
+        let mut active = 0;
+        for &(ref ranking, weight) in rankings {
+            if let Some(team) = ranking.iter().find(|team| remaining.contains(team)) {
+                let i = remaining.iter().position(|r| r == team).unwrap();
+                counts[i] += weight;
+                active += weight;
+            }
+        }
+
+        let (leader, &leader_votes) = counts.iter().enumerate()
+            .max_by(|l, r| l.1.cmp(r.1).then(r.0.cmp(&l.0)))
+            .expect("At least one candidate remains");
+        if remaining.len() == 1 || leader_votes * 2 > active {
+            let mut result = vec![(remaining[leader], leader_votes)];
+            let mut others: Vec<(PublicKey, u64)> = remaining.iter()
+                .zip(counts.iter())
+                .enumerate()
+                .filter(|&(i, _)| i != leader)
+                .map(|(_, (team, votes))| (*team, *votes))
+                .collect();
+            others.sort_by(|l, r| r.1.cmp(&l.1));
+            result.extend(others);
+            result.extend(eliminated.into_iter().rev());
+            return result;
+        }
+
+        let (last, &last_votes) = counts.iter().enumerate()
+            .min_by(|l, r| l.1.cmp(r.1).then(r.0.cmp(&l.0)))
+            .expect("At least one candidate remains");
+        eliminated.push((remaining.remove(last), last_votes));
+    }
+    Vec::new()
+}
+
@@ -1,10 +1,11 @@
-use exonum::{blockchain::{ExecutionResult, Transaction},
+use exonum::{blockchain::{ExecutionResult, Schema, Transaction},
              messages::Message,
-             storage::Fork,
-             crypto::{Hash, CryptoHash, PublicKey}};
+             storage::{Fork, Snapshot},
+             crypto::{CryptoHash, Hash, PublicKey}};
 
 
 use constants::SERVICE_ID;
+use election::*;
 use errors::*;
 use schema::*;
 use wallet::*;
@@ -17,14 +18,213 @@ transactions! {
         struct TxCreateWallet {
             pub_key: &PublicKey,
             name: &str,
-            is_team: bool
         }
 
         struct TxVote {
             from: &PublicKey,
+            election_id: u64,
             to: &PublicKey,
             seed: u64,
         }
+
+        struct TxCreateElection {
+            author: &PublicKey,
+            title: &str,
+            candidates: Vec<PublicKey>,
+            start_height: u64,
+            end_height: u64,
+            max_votes: u64,
+            ballot_mode: u8,
+            reveal_height: u64,
+            seed: u64,
+        }
+
+        struct TxRegisterTeam {
+            admin: &PublicKey,
+            pub_key: &PublicKey,
+            name: &str,
+            seed: u64,
+        }
+
+        struct TxChangeVote {
+            from: &PublicKey,
+            election_id: u64,
+            old_team: &PublicKey,
+            new_team: &PublicKey,
+            seed: u64,
+        }
+
+        struct TxRevokeVote {
+            from: &PublicKey,
+            election_id: u64,
+            seed: u64,
+        }
+
+        struct TxMultiVote {
+            from: &PublicKey,
+            election_id: u64,
+            allocations: Vec<TeamVotes>,
+            seed: u64,
+        }
+
+        struct TxRankedVote {
+            from: &PublicKey,
+            election_id: u64,
+            ranking: Vec<PublicKey>,
+            seed: u64,
+        }
+
+        struct TxCommitVote {
+            from: &PublicKey,
+            election_id: u64,
+            commitment: &Hash,
+            seed: u64,
+        }
+
+        struct TxRevealVote {
+            from: &PublicKey,
+            election_id: u64,
+            team: &PublicKey,
+            salt: &Hash,
+            seed: u64,
+        }
+
+        struct TxDelegate {
+            from: &PublicKey,
+            to: &PublicKey,
+            seed: u64,
+        }
+
+        struct TxUndelegate {
+            from: &PublicKey,
+            seed: u64,
+        }
+    }
+}
+
+
+/// Height of the block which is being created.
+fn current_height(view: &Fork) -> u64 {
+    Schema::new(view).height().next().0
+}
+
+
+/// Returns the election if it accepts votes in the current block.
+fn open_election<T: AsRef<Snapshot>>(schema: &VotesSchema<T>, election_id: u64, height: u64)
+                                     -> Result<Election, Error> {
+    let election = schema.election(election_id).ok_or(Error::ElectionNotFound)?;
+    if height < election.start_height() {
+        Err(Error::VotingNotStarted)
+    } else if !election.is_open(height) {
+        Err(Error::VotingClosed)
+    } else {
+        Ok(election)
+    }
+}
+
+
+/// Checks that the election accepts ballots of the given mode in plain text.
+fn check_mode(election: &Election, mode: BallotMode) -> Result<(), Error> {
+    if election.mode() != mode || election.is_secret() {
+        Err(Error::WrongBallotMode)
+    } else {
+        Ok(())
+    }
+}
+
+
+/// Checks that the vote of the fan in the election isn't carried by a delegate.
+/// A fan who has delegated the vote may still vote on its own until a ballot
+/// of the delegates takes the vote, e.g. after delegating to a fan who has
+/// already voted.
+fn check_own_vote<T: AsRef<Snapshot>>(schema: &VotesSchema<T>, election: &Election,
+                                      fan: &FanWallet) -> Result<(), Error> {
+    if schema.represented_fans(election.id()).contains(fan.pub_key()) {
+        Err(Error::VotedByDelegate)
+    } else {
+        Ok(())
+    }
+}
+
+
+/// Returns the ballot to which the fan adds votes. The first vote of the fan
+/// in the election also carries the votes of the returned delegators.
+fn prepare_ballot<T: AsRef<Snapshot>>(schema: &VotesSchema<T>, election: &Election,
+                                      fan: &FanWallet) -> Result<(Ballot, Vec<PublicKey>), Error> {
+    check_own_vote(schema, election, fan)?;
+    match schema.ballot(election.id(), fan.pub_key()) {
+        Some(ref ballot) if ballot.voted() => Ok((ballot.clone(), Vec::new())),
+        ballot => {
+            let delegators = schema.delegated_votes(election.id(), fan.pub_key());
+            let ballot = ballot.unwrap_or_else(|| Ballot::empty(election.id(), fan.pub_key()))
+                .with_weight(1 + delegators.len() as u64);
+            Ok((ballot, delegators))
+        }
+    }
+}
+
+
+/// Checks that the team is registered and runs in the election.
+fn check_candidate<T: AsRef<Snapshot>>(schema: &VotesSchema<T>, election: &Election,
+                                       team: &PublicKey) -> Result<(), Error> {
+    if schema.team_wallet(team).is_none() {
+        Err(Error::TeamNotFound)
+    } else if !election.has_candidate(team) {
+        Err(Error::CandidateNotFound)
+    } else {
+        Ok(())
+    }
+}
+
+
+/// Checks the parameters of a new election. Elections created by transactions
+/// and the genesis election of `VotesConfig` pass the same checks.
+pub fn validate_election<T: AsRef<Snapshot>>(schema: &VotesSchema<T>, election: &Election)
+                                             -> Result<(), Error> {
+    let candidates = election.candidates();
+
+    if election.start_height() >= election.end_height() {
+        Err(Error::InvalidVotingWindow)?
+    }
+
+    if election.max_votes() == 0 {
+        Err(Error::InvalidMaxVotes)?
+    }
+
+    let mode = BallotMode::from_u8(election.ballot_mode()).ok_or(Error::InvalidBallotMode)?;
+
+    // Secret elections need non-empty commit and reveal phases and plain ballots.
+    if election.is_secret() {
+        if election.reveal_height() <= election.start_height()
+            || election.reveal_height() >= election.end_height() {
+            Err(Error::InvalidVotingWindow)?
+        }
+        if mode != BallotMode::Single {
+            Err(Error::WrongBallotMode)?
+        }
+    }
+
+    for (i, team) in candidates.iter().enumerate() {
+        if schema.team_wallet(team).is_none() {
+            Err(Error::TeamNotFound)?
+        }
+        if candidates[..i].contains(team) {
+            Err(Error::DuplicateCandidate)?
+        }
+    }
+    Ok(())
+}
+
+
+/// Returns the number of tally votes a ballot of the given weight gives to the team.
+/// Tallies are logged as signed deltas, so totals must fit in `i64`.
+fn tally_votes<T: AsRef<Snapshot>>(schema: &VotesSchema<T>, election_id: u64, team: &PublicKey,
+                                   votes: u64, weight: u64) -> Result<u64, Error> {
+    let tally = schema.tally(election_id, team).map_or(0, |tally| tally.votes());
+    let votes = votes.checked_mul(weight).ok_or(Error::VoteCountOverflow)?;
+    match tally.checked_add(votes) {
+        Some(total) if total <= i64::max_value() as u64 => Ok(votes),
+        _ => Err(Error::VoteCountOverflow),
     }
 }
 
@@ -37,25 +237,42 @@ impl Transaction for TxCreateWallet {
     fn execute(&self, view: &mut Fork) -> ExecutionResult {
         println!("{:?}", self);
         let mut schema = VotesSchema::new(view);
-        if self.is_team() {
-            if schema.team_wallet(self.pub_key()).is_none() {
-                let wallet = TeamWallet::new(self.pub_key(), self.name(), 0);
-                println!("Create the team: {:?}", wallet);
-                schema.team_wallets_mut().put(self.pub_key(), wallet);
-                Ok(())
-            } else {
-                Err(Error::WalletAlreadyExists)?
-            }
+        if schema.team_wallet(self.pub_key()).is_some() {
+            Err(Error::KeyRegisteredAsOtherKind)?
+        }
+        if schema.fan_wallet(self.pub_key()).is_none() {
+            let wallet = FanWallet::create(self.pub_key(), self.name());
+            println!("Create the fan: {:?}", wallet);
+            schema.fan_wallets_mut().put(self.pub_key(), wallet);
+            Ok(())
         } else {
-            if schema.fan_wallet(self.pub_key()).is_none() {
-                let wallet = FanWallet::new(self.pub_key(), self.name(), false,
-                                            &Hash::zero().to_hex());
-                println!("Create the fan: {:?}", wallet);
-                schema.fan_wallets_mut().put(self.pub_key(), wallet);
-                Ok(())
-            } else {
-                Err(Error::WalletAlreadyExists)?
-            }
+            Err(Error::FanAlreadyExists)?
+        }
+    }
+}
+
+
+impl Transaction for TxRegisterTeam {
+    fn verify(&self) -> bool {
+        self.verify_signature(self.admin())
+    }
+
+    fn execute(&self, view: &mut Fork) -> ExecutionResult {
+        println!("{:?}", self);
+        let mut schema = VotesSchema::new(view);
+        if !schema.is_admin(self.admin()) {
+            Err(Error::Unauthorized)?
+        }
+        if schema.fan_wallet(self.pub_key()).is_some() {
+            Err(Error::KeyRegisteredAsOtherKind)?
+        }
+        if schema.team_wallet(self.pub_key()).is_none() {
+            let wallet = TeamWallet::new(self.pub_key(), self.name());
+            println!("Create the team: {:?}", wallet);
+            schema.team_wallets_mut().put(self.pub_key(), wallet);
+            Ok(())
+        } else {
+            Err(Error::TeamAlreadyExists)?
         }
     }
 }
@@ -67,27 +284,342 @@ impl Transaction for TxVote {
     }
 
     fn execute(&self, view: &mut Fork) -> ExecutionResult {
+        let height = current_height(view);
         let mut schema = VotesSchema::new(view);
 
-        let sender = match schema.fan_wallet(self.from()) {
-            Some(val) => val,
-            None => Err(Error::SenderNotFound)?,
+        let fan = schema.fan_wallet(self.from()).ok_or(Error::FanNotFound)?;
+
+        let election = open_election(&schema, self.election_id(), height)?;
+        check_mode(&election, BallotMode::Single)?;
+        check_candidate(&schema, &election, self.to())?;
+
+        let (ballot, delegators) = prepare_ballot(&schema, &election, &fan)?;
+
+        if ballot.votes() < election.max_votes() {
+            let votes = tally_votes(&schema, election.id(), self.to(), 1, ballot.weight())?;
+            println!("Vote: {:?} => {:?}", self.from(), self.to());
+            schema.add_represented_fans(election.id(), self.from(), &delegators);
+            schema.save_ballot(ballot.add_votes(self.to(), 1), &self.hash(), height);
+            schema.add_tally_votes(election.id(), self.to(), self.from(), &self.hash(), votes, height);
+            Ok(())
+        } else {
+            Err(Error::AlreadyVoted)?
+        }
+    }
+}
+
+
+impl Transaction for TxChangeVote {
+    fn verify(&self) -> bool {
+        self.verify_signature(self.from())
+    }
+
+    fn execute(&self, view: &mut Fork) -> ExecutionResult {
+        let height = current_height(view);
+        let mut schema = VotesSchema::new(view);
+
+        if schema.fan_wallet(self.from()).is_none() {
+            Err(Error::FanNotFound)?
+        }
+        if self.old_team() == self.new_team() {
+            Err(Error::SameTeam)?
+        }
+
+        let election = open_election(&schema, self.election_id(), height)?;
+        if election.is_secret() || election.mode() == BallotMode::Ranked {
+            Err(Error::WrongBallotMode)?
+        }
+        check_candidate(&schema, &election, self.new_team())?;
+
+        let ballot = match schema.ballot(election.id(), self.from()) {
+            Some(ref val) if val.votes_for(self.old_team()) > 0 => val.clone(),
+            _ => Err(Error::VoteNotFound)?,
         };
 
-        let receiver = match schema.team_wallet(self.to()) {
+        let weight = ballot.weight();
+        let votes = tally_votes(&schema, election.id(), self.new_team(), 1, weight)?;
+        println!("Change vote: {:?} => {:?}", self.old_team(), self.new_team());
+        let ballot = ballot.remove_votes(self.old_team(), 1).add_votes(self.new_team(), 1);
+        schema.save_ballot(ballot, &self.hash(), height);
+        schema.remove_tally_votes(election.id(), self.old_team(), self.from(), &self.hash(), weight,
+                                  height);
+        schema.add_tally_votes(election.id(), self.new_team(), self.from(), &self.hash(), votes,
+                               height);
+        Ok(())
+    }
+}
+
+
+impl Transaction for TxRevokeVote {
+    fn verify(&self) -> bool {
+        self.verify_signature(self.from())
+    }
+
+    fn execute(&self, view: &mut Fork) -> ExecutionResult {
+        let height = current_height(view);
+        let mut schema = VotesSchema::new(view);
+
+        if schema.fan_wallet(self.from()).is_none() {
+            Err(Error::FanNotFound)?
+        }
+
+        let election = open_election(&schema, self.election_id(), height)?;
+        if election.is_secret() || election.mode() == BallotMode::Ranked {
+            Err(Error::WrongBallotMode)?
+        }
+
+        let ballot = match schema.ballot(election.id(), self.from()) {
+            Some(ref val) if val.revoked() => Err(Error::VoteAlreadyRevoked)?,
             Some(val) => val,
-            None => Err(Error::ReceiverNotFound)?,
+            None => Err(Error::NotVoted)?,
         };
 
-        if !sender.voted() {
-            let sender = sender.vote(self.hash());
-            let receiver = receiver.add_vote();
-            println!("Vote: {:?} => {:?}", sender, receiver);
-            schema.fan_wallets_mut().put(self.from(), sender);
-            schema.team_wallets_mut().put(self.to(), receiver);
-            Ok(())
-        } else {
-            Err(Error::InsufficientCurrencyAmount)?
+        println!("Revoke votes: {:?}", self.from());
+        for choice in ballot.choices() {
+            schema.remove_tally_votes(election.id(), choice.team(), self.from(), &self.hash(),
+                                      choice.votes() * ballot.weight(), height);
+        }
+        schema.release_represented_fans(election.id(), self.from());
+        schema.save_ballot(ballot.clear(), &self.hash(), height);
+        Ok(())
+    }
+}
+
+
+impl Transaction for TxMultiVote {
+    fn verify(&self) -> bool {
+        self.verify_signature(self.from())
+    }
+
+    fn execute(&self, view: &mut Fork) -> ExecutionResult {
+        let height = current_height(view);
+        let mut schema = VotesSchema::new(view);
+        let allocations = self.allocations();
+
+        let fan = schema.fan_wallet(self.from()).ok_or(Error::FanNotFound)?;
+
+        let election = open_election(&schema, self.election_id(), height)?;
+        check_mode(&election, BallotMode::Points)?;
+
+        if allocations.is_empty() {
+            Err(Error::InvalidAllocation)?
+        }
+
+        let (mut ballot, delegators) = prepare_ballot(&schema, &election, &fan)?;
+        let mut votes = ballot.votes();
+        let mut tallied = Vec::with_capacity(allocations.len());
+        for (i, allocation) in allocations.iter().enumerate() {
+            check_candidate(&schema, &election, allocation.team())?;
+            if allocation.votes() == 0
+                || allocations[..i].iter().any(|other| other.team() == allocation.team()) {
+                Err(Error::InvalidAllocation)?
+            }
+            votes = votes.saturating_add(allocation.votes());
+            tallied.push(tally_votes(&schema, election.id(), allocation.team(), allocation.votes(),
+                                     ballot.weight())?);
+        }
+        if votes > election.max_votes() {
+            Err(Error::VoteLimitExceeded)?
+        }
+
+        println!("Multi vote: {:?} => {:?}", self.from(), allocations);
+        schema.add_represented_fans(election.id(), self.from(), &delegators);
+        for (allocation, votes) in allocations.iter().zip(tallied) {
+            ballot = ballot.add_votes(allocation.team(), allocation.votes());
+            schema.add_tally_votes(election.id(), allocation.team(), self.from(), &self.hash(),
+                                   votes, height);
+        }
+        schema.save_ballot(ballot, &self.hash(), height);
+        Ok(())
+    }
+}
+
+
+impl Transaction for TxRankedVote {
+    fn verify(&self) -> bool {
+        self.verify_signature(self.from())
+    }
+
+    fn execute(&self, view: &mut Fork) -> ExecutionResult {
+        let height = current_height(view);
+        let mut schema = VotesSchema::new(view);
+        let ranking = self.ranking();
+
+        let fan = schema.fan_wallet(self.from()).ok_or(Error::FanNotFound)?;
+
+        let election = open_election(&schema, self.election_id(), height)?;
+        check_mode(&election, BallotMode::Ranked)?;
+        check_own_vote(&schema, &election, &fan)?;
+
+        if ranking.is_empty() {
+            Err(Error::InvalidRanking)?
+        }
+        for (i, team) in ranking.iter().enumerate() {
+            check_candidate(&schema, &election, team)?;
+            if ranking[..i].contains(team) {
+                Err(Error::InvalidRanking)?
+            }
+        }
+
+        // Tallies of ranked elections count first preferences. The first ranking
+        // of the fan also carries the votes of the delegators.
+        let previous = schema.ranking(election.id(), self.from());
+        let (weight, delegators) = match previous {
+            Some(ref previous) => (previous.weight(), Vec::new()),
+            None => {
+                let delegators = schema.delegated_votes(election.id(), self.from());
+                (1 + delegators.len() as u64, delegators)
+            }
+        };
+        let votes = tally_votes(&schema, election.id(), &ranking[0], 1, weight)?;
+        if let Some(previous) = previous {
+            schema.remove_tally_votes(election.id(), &previous.teams()[0], self.from(),
+                                      &self.hash(), weight, height);
         }
+        println!("Ranked vote: {:?} => {:?}", self.from(), ranking);
+        schema.add_represented_fans(election.id(), self.from(), &delegators);
+        schema.add_tally_votes(election.id(), &ranking[0], self.from(), &self.hash(), votes, height);
+        let ranking = Ranking::new(election.id(), self.from(), ranking, weight);
+        schema.save_ranking(ranking, &self.hash());
+        Ok(())
+    }
+}
+
+
+impl Transaction for TxDelegate {
+    fn verify(&self) -> bool {
+        self.verify_signature(self.from())
+    }
+
+    fn execute(&self, view: &mut Fork) -> ExecutionResult {
+        let mut schema = VotesSchema::new(view);
+
+        if schema.fan_wallet(self.from()).is_none() {
+            Err(Error::FanNotFound)?
+        }
+        if schema.fan_wallet(self.to()).is_none() {
+            Err(Error::DelegateNotFound)?
+        }
+        if schema.delegation_chain(self.to()).contains(self.from()) {
+            Err(Error::DelegationCycle)?
+        }
+
+        println!("Delegate the vote: {:?} => {:?}", self.from(), self.to());
+        schema.set_delegate(self.from(), self.to());
+        Ok(())
+    }
+}
+
+
+impl Transaction for TxUndelegate {
+    fn verify(&self) -> bool {
+        self.verify_signature(self.from())
+    }
+
+    fn execute(&self, view: &mut Fork) -> ExecutionResult {
+        let mut schema = VotesSchema::new(view);
+
+        match schema.fan_wallet(self.from()) {
+            Some(ref fan) if fan.has_delegate() => {}
+            Some(_) => Err(Error::NotDelegated)?,
+            None => Err(Error::FanNotFound)?,
+        }
+
+        println!("Undelegate the vote: {:?}", self.from());
+        schema.set_delegate(self.from(), self.from());
+        Ok(())
+    }
+}
+
+
+impl Transaction for TxCommitVote {
+    fn verify(&self) -> bool {
+        self.verify_signature(self.from())
+    }
+
+    fn execute(&self, view: &mut Fork) -> ExecutionResult {
+        let height = current_height(view);
+        let mut schema = VotesSchema::new(view);
+
+        let fan = schema.fan_wallet(self.from()).ok_or(Error::FanNotFound)?;
+
+        let election = open_election(&schema, self.election_id(), height)?;
+        if !election.is_secret() {
+            Err(Error::WrongBallotMode)?
+        }
+        check_own_vote(&schema, &election, &fan)?;
+        if height >= election.reveal_height() {
+            Err(Error::CommitPhaseOver)?
+        }
+
+        println!("Commit vote: {:?} => {:?}", self.from(), self.commitment());
+        let commitment = Commitment::new(election.id(), self.from(), self.commitment(), false);
+        schema.save_commitment(commitment);
+        Ok(())
+    }
+}
+
+
+impl Transaction for TxRevealVote {
+    fn verify(&self) -> bool {
+        self.verify_signature(self.from())
+    }
+
+    fn execute(&self, view: &mut Fork) -> ExecutionResult {
+        let height = current_height(view);
+        let mut schema = VotesSchema::new(view);
+
+        let fan = schema.fan_wallet(self.from()).ok_or(Error::FanNotFound)?;
+
+        let election = open_election(&schema, self.election_id(), height)?;
+        if !election.is_secret() {
+            Err(Error::WrongBallotMode)?
+        }
+        check_own_vote(&schema, &election, &fan)?;
+        if height < election.reveal_height() {
+            Err(Error::RevealNotStarted)?
+        }
+
+        let commitment = match schema.commitment(election.id(), self.from()) {
+            Some(ref val) if val.revealed() => Err(Error::VoteAlreadyRevealed)?,
+            Some(val) => val,
+            None => Err(Error::CommitmentNotFound)?,
+        };
+        if *commitment.commitment() != vote_commitment(self.team(), self.salt()) {
+            Err(Error::CommitmentMismatch)?
+        }
+        check_candidate(&schema, &election, self.team())?;
+
+        let delegators = schema.delegated_votes(election.id(), self.from());
+        let weight = 1 + delegators.len() as u64;
+        let votes = tally_votes(&schema, election.id(), self.team(), 1, weight)?;
+        println!("Reveal vote: {:?} => {:?}", self.from(), self.team());
+        let ballot = Ballot::empty(election.id(), self.from())
+            .with_weight(weight)
+            .add_votes(self.team(), 1);
+        schema.save_commitment(commitment.reveal());
+        schema.add_represented_fans(election.id(), self.from(), &delegators);
+        schema.save_ballot(ballot, &self.hash(), height);
+        schema.add_tally_votes(election.id(), self.team(), self.from(), &self.hash(), votes, height);
+        Ok(())
+    }
+}
+
+
+impl Transaction for TxCreateElection {
+    fn verify(&self) -> bool {
+        self.verify_signature(self.author())
+    }
+
+    fn execute(&self, view: &mut Fork) -> ExecutionResult {
+        let height = current_height(view);
+        let mut schema = VotesSchema::new(view);
+        let election = Election::new(schema.next_election_id(), self.title(), self.candidates(),
+                                     self.start_height(), self.end_height(), self.max_votes(),
+                                     self.ballot_mode(), self.reveal_height(), height);
+        validate_election(&schema, &election)?;
+        println!("Create the election: {:?}", election);
+        schema.add_election(election);
+        Ok(())
     }
 }
@@ -1,12 +1,15 @@
-use exonum::crypto::{Hash, PublicKey};
+use exonum::crypto::PublicKey;
 
 
 encoding_struct! {
+    /// Wallet of a fan. `delegate` is the fan who votes on behalf of the wallet
+    /// owner, or the owner itself if the vote isn't delegated. `weight` is the number
+    /// of fans, including the owner, who delegated their votes to the owner.
     struct FanWallet {
         pub_key: &PublicKey,
         name: &str,
-        voted: bool,
-        vote_hash: &str
+        delegate: &PublicKey,
+        weight: u64,
     }
 }
 
@@ -15,29 +18,28 @@ encoding_struct! {
     struct TeamWallet {
         pub_key: &PublicKey,
         name: &str,
-        votes: u64,
     }
 }
 
 
 impl FanWallet {
-    pub fn vote(self, vote_tx_hash: Hash) -> Self {
-        Self::new(
-            self.pub_key(),
-            self.name(),
-            true,
-            &vote_tx_hash.to_hex()
-        )
+    pub fn create(pub_key: &PublicKey, name: &str) -> Self {
+        Self::new(pub_key, name, pub_key, 1)
+    }
+
+    pub fn has_delegate(&self) -> bool {
+        self.delegate() != self.pub_key()
     }
-}
 
+    pub fn set_delegate(self, delegate: &PublicKey) -> Self {
+        Self::new(self.pub_key(), self.name(), delegate, self.weight())
+    }
+
+    pub fn add_weight(self, weight: u64) -> Self {
+        Self::new(self.pub_key(), self.name(), self.delegate(), self.weight() + weight)
+    }
 
-impl TeamWallet {
-    pub fn add_vote(self) -> Self {
-        Self::new(
-            self.pub_key(),
-            self.name(),
-            self.votes() + 1
-        )
+    pub fn remove_weight(self, weight: u64) -> Self {
+        Self::new(self.pub_key(), self.name(), self.delegate(), self.weight() - weight)
     }
 }
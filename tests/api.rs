@@ -5,11 +5,23 @@ extern crate football_voting;
 #[macro_use] extern crate serde_json;
 
 
-use exonum::crypto::{self, PublicKey, SecretKey, Hash, CryptoHash};
+use exonum::blockchain::{Block, Blockchain};
+use exonum::crypto::{self, PublicKey, SecretKey, Hash, CryptoHash, Seed};
+use exonum::helpers::Height;
 use exonum_testkit::{ApiKind, TestKit, TestKitApi, TestKitBuilder};
-use football_voting::transactions::{TxCreateWallet, TxVote};
+use football_voting::transactions::{TxCreateWallet, TxVote, TxCreateElection, TxRegisterTeam,
+                                    TxChangeVote, TxRankedVote, TxCommitVote, TxDelegate};
 use football_voting::service::VotesService;
-use football_voting::constants::SERVICE_NAME;
+use football_voting::api::{BallotInfo, BatchItemResponse, ErrorInfo, FanWalletProof,
+                           TeamWalletProof, TallyProof, TeamRank, TeamRating, TransactionStatus,
+                           VotesPoint, WalletInfo, WalletsPage};
+use football_voting::client;
+use football_voting::config::VotesConfig;
+use football_voting::constants::{SERVICE_ID, SERVICE_NAME, FAN_WALLETS_TABLE, TEAM_WALLETS_TABLE,
+                                 TALLIES_TABLE, MAX_BATCH_SIZE};
+use football_voting::election::{vote_commitment, BallotMode, Election};
+use football_voting::errors::Error;
+use football_voting::schema::{election_key, VotesSchema};
 use football_voting::wallet::{FanWallet, TeamWallet};
 
 
@@ -18,9 +30,27 @@ struct VotesApi {
 }
 
 
+fn validator_keys(testkit: &TestKit) -> Vec<PublicKey> {
+    testkit.network()
+        .validators()
+        .iter()
+        .map(|validator| validator.public_keys().consensus_key)
+        .collect()
+}
+
+
+fn admin_keypair() -> (PublicKey, SecretKey) {
+    crypto::gen_keypair_from_seed(&Seed::new([1; 32]))
+}
+
+
 fn create_testkit() -> (TestKit, VotesApi) {
+    let (admin_pubkey, _) = admin_keypair();
     let testkit = TestKitBuilder::validator()
-        .with_service(VotesService)
+        .with_service(VotesService::new(VotesConfig {
+            admins: vec![admin_pubkey],
+            ..Default::default()
+        }))
         .create();
     let api = VotesApi {
         inner: testkit.api(),
@@ -32,7 +62,7 @@ fn create_testkit() -> (TestKit, VotesApi) {
 impl VotesApi{
     fn create_fan_wallet(&self, name: &str) -> (TxCreateWallet, SecretKey) {
         let (pubkey, key) = crypto::gen_keypair();
-        let tx = TxCreateWallet::new(&pubkey, name, false, &key);
+        let tx = TxCreateWallet::new(&pubkey, name, &key);
         let tx_info: serde_json::Value = self.inner.post(
             ApiKind::Service(SERVICE_NAME), "v1/create", &tx
         );
@@ -40,16 +70,37 @@ impl VotesApi{
         (tx, key)
     }
 
-    fn create_team_wallet(&self, name: &str) -> (TxCreateWallet, SecretKey) {
+    fn create_team_wallet(&self, name: &str) -> (TxRegisterTeam, SecretKey) {
         let (pubkey, key) = crypto::gen_keypair();
-        let tx = TxCreateWallet::new(&pubkey, name, true, &key);
+        let (admin_pubkey, admin_key) = admin_keypair();
+        let tx = TxRegisterTeam::new(&admin_pubkey, &pubkey, name, 0, &admin_key);
         let tx_info: serde_json::Value = self.inner.post(
-            ApiKind::Service(SERVICE_NAME), "v1/create", &tx
+            ApiKind::Service(SERVICE_NAME), "v1/team/register", &tx
         );
         assert_eq!(tx_info, json!({ "tx_hash": tx.hash() }));
         (tx, key)
     }
 
+    fn create_election(&self, title: &str, candidates: Vec<PublicKey>) -> TxCreateElection {
+        let (pubkey, key) = crypto::gen_keypair();
+        let tx = TxCreateElection::new(&pubkey, title, candidates, 0, u64::max_value(), 1,
+                                       BallotMode::Single as u8, 0, 0, &key);
+        let tx_info: serde_json::Value = self.inner.post(
+            ApiKind::Service(SERVICE_NAME), "v1/election/create", &tx
+        );
+        assert_eq!(tx_info, json!({ "tx_hash": tx.hash() }));
+        tx
+    }
+
+    fn vote(&self, from: &PublicKey, election_id: u64, to: &PublicKey, key: &SecretKey) -> TxVote {
+        let tx = TxVote::new(from, election_id, to, 0, key);
+        let tx_info: serde_json::Value = self.inner.post(
+            ApiKind::Service(SERVICE_NAME), "v1/vote", &tx
+        );
+        assert_eq!(tx_info, json!({ "tx_hash": tx.hash() }));
+        tx
+    }
+
     fn get_fan_wallet(&self, pubkey: &PublicKey) -> FanWallet {
         self.inner.get(
             ApiKind::Service(SERVICE_NAME),
@@ -63,6 +114,48 @@ impl VotesApi{
             &format!("v1/team/wallet/{}", pubkey.to_string()),
         )
     }
+
+    fn get_ballot(&self, pubkey: &PublicKey, election_id: u64) -> BallotInfo {
+        self.inner.get(
+            ApiKind::Service(SERVICE_NAME),
+            &format!("v1/fan/wallet/{}/ballot?election_id={}", pubkey.to_string(), election_id),
+        )
+    }
+
+    fn get_rating(&self, election_id: u64) -> Vec<TeamRating> {
+        self.inner.get(
+            ApiKind::Service(SERVICE_NAME),
+            &format!("v1/rating?election_id={}", election_id),
+        )
+    }
+
+    fn get_transaction_status(&self, tx_hash: &Hash) -> TransactionStatus {
+        self.inner.get(
+            ApiKind::Service(SERVICE_NAME),
+            &format!("v1/transaction/{}", tx_hash.to_string()),
+        )
+    }
+
+    fn get_fan_wallet_proof(&self, pubkey: &PublicKey) -> FanWalletProof {
+        self.inner.get(
+            ApiKind::Service(SERVICE_NAME),
+            &format!("v1/fan/wallet/{}/proof", pubkey.to_string()),
+        )
+    }
+
+    fn get_team_wallet_proof(&self, pubkey: &PublicKey) -> TeamWalletProof {
+        self.inner.get(
+            ApiKind::Service(SERVICE_NAME),
+            &format!("v1/team/wallet/{}/proof", pubkey.to_string()),
+        )
+    }
+
+    fn get_tally_proof(&self, pubkey: &PublicKey, election_id: u64) -> serde_json::Value {
+        self.inner.get(
+            ApiKind::Service(SERVICE_NAME),
+            &format!("v1/team/wallet/{}/tally/proof?election_id={}", pubkey.to_string(), election_id),
+        )
+    }
 }
 
 
@@ -74,8 +167,45 @@ fn test_create_fan_wallet() {
     let wallet = api.get_fan_wallet(tx.pub_key());
     assert_eq!(wallet.pub_key(), tx.pub_key());
     assert_eq!(wallet.name(), tx.name());
-    assert_eq!(wallet.voted(), false);
-    assert_eq!(wallet.vote_hash(), &Hash::zero().to_hex());
+    assert_eq!(wallet.delegate(), tx.pub_key());
+    assert_eq!(wallet.weight(), 1);
+}
+
+
+#[test]
+fn test_post_transactions_batch() {
+    let (mut testkit, api) = create_testkit();
+    let (alice_pubkey, alice_key) = crypto::gen_keypair();
+    let (bob_pubkey, _) = crypto::gen_keypair();
+    let (_, other_key) = crypto::gen_keypair();
+    let alice_tx = TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key);
+    let forged_tx = TxCreateWallet::new(&bob_pubkey, "Bob", &other_key);
+    let batch = json!([alice_tx, forged_tx, { "body": {} }]);
+    let results: Vec<BatchItemResponse> = api.inner.post(
+        ApiKind::Service(SERVICE_NAME), "v1/transactions/batch", &batch
+    );
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].tx_hash, Some(alice_tx.hash()));
+    assert_eq!(results[0].error, None);
+    assert_eq!(results[1].tx_hash, Some(forged_tx.hash()));
+    assert_eq!(results[1].error, Some("Invalid signature".to_owned()));
+    assert_eq!(results[2].tx_hash, None);
+    assert!(results[2].error.is_some());
+    testkit.create_block();
+    assert_eq!(api.get_fan_wallet(&alice_pubkey).name(), "Alice");
+    let snapshot = testkit.snapshot();
+    assert!(VotesSchema::new(&snapshot).fan_wallet(&bob_pubkey).is_none());
+}
+
+
+#[test]
+#[should_panic]
+fn test_post_too_large_transactions_batch() {
+    let (_, api) = create_testkit();
+    let batch = vec![json!({ "body": {} }); MAX_BATCH_SIZE + 1];
+    let _: Vec<BatchItemResponse> = api.inner.post(
+        ApiKind::Service(SERVICE_NAME), "v1/transactions/batch", &batch
+    );
 }
 
 
@@ -87,7 +217,24 @@ fn test_create_team_wallet() {
     let wallet = api.get_team_wallet(tx.pub_key());
     assert_eq!(wallet.pub_key(), tx.pub_key());
     assert_eq!(wallet.name(), tx.name());
-    assert_eq!(wallet.votes(), 0);
+}
+
+
+#[test]
+fn test_create_election() {
+    let (mut testkit, api) = create_testkit();
+    let (team1_tx, _) = api.create_team_wallet("Wonderland");
+    let (team2_tx, _) = api.create_team_wallet("Underland");
+    testkit.create_block();
+    api.create_election("World Cup winner", vec![*team1_tx.pub_key(), *team2_tx.pub_key()]);
+    api.create_election("Best goal", vec![*team2_tx.pub_key()]);
+    testkit.create_block();
+    let election: Election = api.inner.get(ApiKind::Service(SERVICE_NAME), "v1/election/0");
+    assert_eq!(election.title(), "World Cup winner");
+    assert_eq!(election.candidates(), vec![*team1_tx.pub_key(), *team2_tx.pub_key()]);
+    let elections: Vec<Election> = api.inner.get(ApiKind::Service(SERVICE_NAME), "v1/elections");
+    assert_eq!(elections.len(), 2);
+    assert_eq!(elections[1].title(), "Best goal");
 }
 
 
@@ -95,21 +242,30 @@ fn test_create_team_wallet() {
 fn test_vote() {
     let (mut testkit, api) = create_testkit();
     let (fan_tx, fan_key) = api.create_fan_wallet("Alice");
-    let (team_tx, team_key) = api.create_team_wallet("Wonderland");
+    let (team_tx, _) = api.create_team_wallet("Wonderland");
     testkit.create_block();
-    let vote_tx = TxVote::new(fan_tx.pub_key(), team_tx.pub_key(), 0, &fan_key);
-    let vote_tx_info: serde_json::Value = api.inner.post(
-        ApiKind::Service(SERVICE_NAME), "v1/vote", &vote_tx
-    );
+    api.create_election("World Cup winner", vec![*team_tx.pub_key()]);
+    testkit.create_block();
+    let vote_tx = api.vote(fan_tx.pub_key(), 0, team_tx.pub_key(), &fan_key);
     testkit.create_block();
-    assert_eq!(vote_tx_info, json!({ "tx_hash": vote_tx.hash() }));
-    // check fan wallet
-    let fan_wallet = api.get_fan_wallet(fan_tx.pub_key());
-    assert_eq!(fan_wallet.voted(), true);
-    assert_eq!(fan_wallet.vote_hash(), vote_tx.hash().to_hex());
-    // check team walelt
-    let team_wallet = api.get_team_wallet(team_tx.pub_key());
-    assert_eq!(team_wallet.votes(), 1);
+    // check fan ballot
+    let ballot = api.get_ballot(fan_tx.pub_key(), 0);
+    assert_eq!(ballot.choices.len(), 1);
+    assert_eq!(ballot.choices[0].team(), team_tx.pub_key());
+    assert_eq!(ballot.choices[0].votes(), 1);
+    assert_eq!(ballot.vote_hash, Some(vote_tx.hash()));
+    assert_eq!(ballot.vote_height, 3);
+    // check block with the vote
+    let block: Block = api.inner.get(
+        ApiKind::Service(SERVICE_NAME),
+        &format!("v1/block/{}?election_id=0", fan_tx.pub_key().to_string()),
+    );
+    assert_eq!(block.height(), Height(3));
+    // check team tally
+    let rating = api.get_rating(0);
+    assert_eq!(rating.len(), 1);
+    assert_eq!(rating[0].pub_key, *team_tx.pub_key());
+    assert_eq!(rating[0].votes, 1);
 }
 
 
@@ -117,36 +273,38 @@ fn test_vote() {
 fn test_vote_for_non_existing_team() {
     let (mut testkit, api) = create_testkit();
     let (fan_tx, fan_key) = api.create_fan_wallet("Alice");
-    let (team_pubkey, team_key) = crypto::gen_keypair();
+    let (team_tx, _) = api.create_team_wallet("Wonderland");
+    let (team_pubkey, _) = crypto::gen_keypair();
     testkit.create_block();
-    let vote_tx = TxVote::new(fan_tx.pub_key(), &team_pubkey, 0, &fan_key);
-    let vote_tx_info: serde_json::Value = api.inner.post(
-        ApiKind::Service(SERVICE_NAME), "v1/vote", &vote_tx
-    );
+    api.create_election("World Cup winner", vec![*team_tx.pub_key()]);
     testkit.create_block();
-    assert_eq!(vote_tx_info, json!({ "tx_hash": vote_tx.hash() }));
-    // check fan wallet
-    let fan_wallet = api.get_fan_wallet(fan_tx.pub_key());
-    assert_eq!(fan_wallet.voted(), false);
-    assert_eq!(fan_wallet.vote_hash(), &Hash::zero().to_hex());
+    api.vote(fan_tx.pub_key(), 0, &team_pubkey, &fan_key);
+    testkit.create_block();
+    // check fan ballot
+    let ballot = api.get_ballot(fan_tx.pub_key(), 0);
+    assert!(ballot.choices.is_empty());
+    assert_eq!(ballot.vote_hash, None);
+    let response: serde_json::Value = api.inner.get(
+        ApiKind::Service(SERVICE_NAME),
+        &format!("v1/fan/wallet/{}/ballot?election_id=0", fan_tx.pub_key().to_string()),
+    );
+    assert_eq!(response["vote_hash"], serde_json::Value::Null);
 }
 
 
 #[test]
 fn test_vote_for_as_existing_fan() {
     let (mut testkit, api) = create_testkit();
-    let (team_tx, team_key) = api.create_team_wallet("Wonderland");
+    let (team_tx, _) = api.create_team_wallet("Wonderland");
     let (fan_pubkey, fan_key) = crypto::gen_keypair();
     testkit.create_block();
-    let vote_tx = TxVote::new(&fan_pubkey, team_tx.pub_key(), 0, &fan_key);
-    let vote_tx_info: serde_json::Value = api.inner.post(
-        ApiKind::Service(SERVICE_NAME), "v1/vote", &vote_tx
-    );
+    api.create_election("World Cup winner", vec![*team_tx.pub_key()]);
     testkit.create_block();
-    assert_eq!(vote_tx_info, json!({ "tx_hash": vote_tx.hash() }));
-    // check fan wallet
-    let team_wallet = api.get_team_wallet(team_tx.pub_key());
-    assert_eq!(team_wallet.votes(), 0);
+    api.vote(&fan_pubkey, 0, team_tx.pub_key(), &fan_key);
+    testkit.create_block();
+    // check team tally
+    let rating = api.get_rating(0);
+    assert_eq!(rating[0].votes, 0);
 }
 
 
@@ -154,35 +312,696 @@ fn test_vote_for_as_existing_fan() {
 fn test_get_rating() {
     let (mut testkit, api) = create_testkit();
     let (fan_tx, fan_key) = api.create_fan_wallet("Alice");
-    let (team1_tx, team1_key) = api.create_team_wallet("Wonderland");
-    let (team2_tx, team2_key) = api.create_team_wallet("Underland");
+    let (team1_tx, _) = api.create_team_wallet("Wonderland");
+    let (team2_tx, _) = api.create_team_wallet("Underland");
     testkit.create_block();
-    let vote_tx = TxVote::new(fan_tx.pub_key(), team1_tx.pub_key(), 0, &fan_key);
-    let vote_tx_info: serde_json::Value = api.inner.post(
-        ApiKind::Service(SERVICE_NAME), "v1/vote", &vote_tx
-    );
+    api.create_election("World Cup winner", vec![*team2_tx.pub_key(), *team1_tx.pub_key()]);
+    testkit.create_block();
+    api.vote(fan_tx.pub_key(), 0, team1_tx.pub_key(), &fan_key);
     testkit.create_block();
     let rating_info: serde_json::Value = api.inner.get(
-        ApiKind::Service(SERVICE_NAME), "v1/rating"
+        ApiKind::Service(SERVICE_NAME), "v1/rating?election_id=0"
     );
     println!("{:?}", rating_info);
     let teams = match rating_info.as_array() {
         Some(x) => x,
         _ => panic!("Rating is not an array")
     };
+    assert_eq!(teams.len(), 2);
+    assert_eq!(teams[0]["name"], "Wonderland");
     for team in teams {
         let team_obj = match team.as_object() {
             Some(x) => x,
             _ => panic!("Team info is not an object")
         };
-        match (team_obj["name"].as_str(), team_obj["votes"].as_str().unwrap().parse::<u64>()) {
-            (Some("Wonderland"), Ok(x)) => if x != 1 {
+        match (team_obj["name"].as_str(), team_obj["votes"].as_u64()) {
+            (Some("Wonderland"), Some(x)) => if x != 1 {
                 panic!("Wonderland have incorrect amount of votes")
             },
-            (Some("Underland"), Ok(x)) => if x != 0 {
+            (Some("Underland"), Some(x)) => if x != 0 {
                 panic!("Underland have incorrect amount of votes")
             },
             _ => panic!(format!("Incorrect team description: {:?}", team_obj))
         }
     }
 }
+
+
+#[test]
+fn test_get_rating_page_and_rank() {
+    let (mut testkit, api) = create_testkit();
+    let fans: Vec<_> = ["Alice", "Bob", "Carol"].iter().map(|name| api.create_fan_wallet(name)).collect();
+    let teams: Vec<_> = ["Wonderland", "Underland", "Overland"].iter()
+        .map(|name| *api.create_team_wallet(name).0.pub_key())
+        .collect();
+    testkit.create_block();
+    api.create_election("World Cup winner", teams.clone());
+    testkit.create_block();
+    api.vote(fans[0].0.pub_key(), 0, &teams[1], &fans[0].1);
+    api.vote(fans[1].0.pub_key(), 0, &teams[1], &fans[1].1);
+    api.vote(fans[2].0.pub_key(), 0, &teams[2], &fans[2].1);
+    testkit.create_block();
+
+    let rating = |query: &str| -> Vec<(PublicKey, u64)> {
+        let rating: Vec<TeamRating> = api.inner.get(
+            ApiKind::Service(SERVICE_NAME),
+            &format!("v1/rating?election_id=0&{}", query),
+        );
+        rating.into_iter().map(|team| (team.pub_key, team.votes)).collect()
+    };
+    assert_eq!(rating("limit=2"), vec![(teams[1], 2), (teams[2], 1)]);
+    assert_eq!(rating("from=1&limit=1"), vec![(teams[2], 1)]);
+    assert_eq!(rating("min_votes=1&max_votes=1"), vec![(teams[2], 1)]);
+    assert_eq!(rating("max_votes=0"), vec![(teams[0], 0)]);
+
+    let rank: TeamRank = api.inner.get(
+        ApiKind::Service(SERVICE_NAME),
+        &format!("v1/team/wallet/{}/rank?election_id=0", teams[2].to_string()),
+    );
+    assert_eq!((rank.rank, rank.votes), (1, 1));
+}
+
+
+#[test]
+fn test_get_rating_at_height() {
+    let (mut testkit, api) = create_testkit();
+    let (alice_tx, alice_key) = api.create_fan_wallet("Alice");
+    let (bob_tx, bob_key) = api.create_fan_wallet("Bob");
+    let teams: Vec<_> = ["Wonderland", "Underland"].iter()
+        .map(|name| *api.create_team_wallet(name).0.pub_key())
+        .collect();
+    testkit.create_block();
+    api.create_election("World Cup winner", teams.clone());
+    testkit.create_block();
+    api.vote(alice_tx.pub_key(), 0, &teams[0], &alice_key);
+    testkit.create_block();
+    api.vote(bob_tx.pub_key(), 0, &teams[1], &bob_key);
+    let change_tx = TxChangeVote::new(alice_tx.pub_key(), 0, &teams[0], &teams[1], 0, &alice_key);
+    let _: serde_json::Value = api.inner.post(
+        ApiKind::Service(SERVICE_NAME), "v1/change_vote", &change_tx
+    );
+    testkit.create_block();
+
+    let rating = |height: u64| -> Vec<(PublicKey, u64)> {
+        let rating: Vec<TeamRating> = api.inner.get(
+            ApiKind::Service(SERVICE_NAME),
+            &format!("v1/rating?election_id=0&height={}", height),
+        );
+        rating.into_iter().map(|team| (team.pub_key, team.votes)).collect()
+    };
+    assert_eq!(rating(3), vec![(teams[0], 1), (teams[1], 0)]);
+    assert_eq!(rating(4), vec![(teams[1], 2), (teams[0], 0)]);
+
+    let error: serde_json::Value = api.inner.get_err(
+        ApiKind::Service(SERVICE_NAME), "v1/rating?election_id=0&height=5"
+    );
+    assert!(error["debug"].as_str().unwrap().contains("not committed yet"));
+    // The election is created in block 2
+    let response: serde_json::Value = api.inner.get_err(
+        ApiKind::Service(SERVICE_NAME), "v1/rating?election_id=0&height=1"
+    );
+    assert_eq!(response, json!("Election not found"));
+}
+
+
+#[test]
+fn test_team_history() {
+    let (mut testkit, api) = create_testkit();
+    let (alice_tx, alice_key) = api.create_fan_wallet("Alice");
+    let (bob_tx, bob_key) = api.create_fan_wallet("Bob");
+    let teams: Vec<_> = ["Wonderland", "Underland"].iter()
+        .map(|name| *api.create_team_wallet(name).0.pub_key())
+        .collect();
+    testkit.create_block();
+    api.create_election("World Cup winner", teams.clone());
+    testkit.create_block();
+    api.vote(alice_tx.pub_key(), 0, &teams[0], &alice_key);
+    testkit.create_block();
+    api.vote(bob_tx.pub_key(), 0, &teams[0], &bob_key);
+    testkit.create_block();
+    let change_tx = TxChangeVote::new(alice_tx.pub_key(), 0, &teams[0], &teams[1], 0, &alice_key);
+    let _: serde_json::Value = api.inner.post(
+        ApiKind::Service(SERVICE_NAME), "v1/change_vote", &change_tx
+    );
+    testkit.create_block();
+
+    let history = |query: &str| -> Vec<(u64, u64)> {
+        let points: Vec<VotesPoint> = api.inner.get(
+            ApiKind::Service(SERVICE_NAME),
+            &format!("v1/team/wallet/{}/history?election_id=0{}", teams[0].to_string(), query),
+        );
+        points.into_iter().map(|point| (point.height, point.votes)).collect()
+    };
+    assert_eq!(history(""), vec![(3, 1), (4, 2), (5, 1)]);
+    assert_eq!(history("&step=2"), vec![(3, 1), (5, 1)]);
+
+    let error: serde_json::Value = api.inner.get_err(
+        ApiKind::Service(SERVICE_NAME),
+        &format!("v1/team/wallet/{}/history?election_id=0&step=0", teams[0].to_string()),
+    );
+    assert!(error["debug"].as_str().unwrap().contains("`step`"));
+}
+
+
+#[test]
+fn test_get_rating_of_non_existing_election() {
+    let (mut testkit, api) = create_testkit();
+    testkit.create_block();
+    let response: serde_json::Value = api.inner.get_err(
+        ApiKind::Service(SERVICE_NAME), "v1/rating?election_id=0"
+    );
+    assert_eq!(response, json!("Election not found"));
+}
+
+
+#[test]
+fn test_fan_wallets_pages() {
+    let (mut testkit, api) = create_testkit();
+    let fans: Vec<_> = ["Alice", "Albert", "Bob"].iter().map(|name| api.create_fan_wallet(name)).collect();
+    let (team_tx, _) = api.create_team_wallet("Wonderland");
+    testkit.create_block();
+    api.create_election("World Cup winner", vec![*team_tx.pub_key()]);
+    testkit.create_block();
+    api.vote(fans[0].0.pub_key(), 0, team_tx.pub_key(), &fans[0].1);
+    testkit.create_block();
+
+    let mut keys: Vec<PublicKey> = fans.iter().map(|&(ref tx, _)| *tx.pub_key()).collect();
+    keys.sort_by(|l, r| l.as_ref().cmp(r.as_ref()));
+    let get_page = |query: &str| -> WalletsPage<FanWallet> {
+        api.inner.get(ApiKind::Service(SERVICE_NAME), &format!("v1/fan/wallets?{}", query))
+    };
+    let page_keys = |page: &WalletsPage<FanWallet>| -> Vec<PublicKey> {
+        page.wallets.iter().map(|wallet| *wallet.pub_key()).collect()
+    };
+
+    let first = get_page("limit=2");
+    assert_eq!(page_keys(&first), keys[..2].to_vec());
+    assert_eq!(first.next, Some(keys[2]));
+    let second = get_page(&format!("limit=2&from={}", keys[2].to_string()));
+    assert_eq!(page_keys(&second), keys[2..].to_vec());
+    assert_eq!(second.next, None);
+    // Larger limits are reduced to `MAX_PAGE_SIZE`
+    let all = get_page(&format!("limit={}", u64::max_value()));
+    assert_eq!(page_keys(&all), keys);
+    assert_eq!(all.next, None);
+
+    let mut names: Vec<String> = get_page("name=Al").wallets.iter()
+        .map(|wallet| wallet.name().to_owned())
+        .collect();
+    names.sort();
+    assert_eq!(names, vec!["Albert", "Alice"]);
+    assert_eq!(page_keys(&get_page("voted=true&election_id=0")), vec![*fans[0].0.pub_key()]);
+    assert_eq!(get_page("voted=false&election_id=0").wallets.len(), 2);
+
+    let response: serde_json::Value = api.inner.get_err(
+        ApiKind::Service(SERVICE_NAME), "v1/fan/wallets?voted=true"
+    );
+    assert!(response["debug"].as_str().unwrap().contains("election_id"));
+}
+
+
+#[test]
+fn test_get_wallet_of_any_kind() {
+    let (mut testkit, api) = create_testkit();
+    let (fan_tx, _) = api.create_fan_wallet("Alice");
+    let (team_tx, _) = api.create_team_wallet("Wonderland");
+    testkit.create_block();
+    let wallet: WalletInfo = api.inner.get(
+        ApiKind::Service(SERVICE_NAME), &format!("v1/wallet/{}", fan_tx.pub_key().to_string())
+    );
+    assert_matches!(wallet, WalletInfo::Fan(ref wallet) if wallet.name() == "Alice");
+    let wallet: WalletInfo = api.inner.get(
+        ApiKind::Service(SERVICE_NAME), &format!("v1/wallet/{}", team_tx.pub_key().to_string())
+    );
+    assert_matches!(wallet, WalletInfo::Team(ref wallet) if wallet.name() == "Wonderland");
+    let (pubkey, _) = crypto::gen_keypair();
+    let response: serde_json::Value = api.inner.get_err(
+        ApiKind::Service(SERVICE_NAME), &format!("v1/wallet/{}", pubkey.to_string())
+    );
+    assert_eq!(response, json!("Wallet not found"));
+}
+
+
+#[test]
+fn test_transaction_status() {
+    let (mut testkit, api) = create_testkit();
+    let (fan_tx, fan_key) = api.create_fan_wallet("Alice");
+    let (team_tx, _) = api.create_team_wallet("Wonderland");
+    let (team_pubkey, _) = crypto::gen_keypair();
+    assert_eq!(api.get_transaction_status(&fan_tx.hash()), TransactionStatus::Pending);
+    testkit.create_block();
+    assert_matches!(
+        api.get_transaction_status(&fan_tx.hash()),
+        TransactionStatus::Success { height: 1, .. }
+    );
+    api.create_election("World Cup winner", vec![*team_tx.pub_key()]);
+    testkit.create_block();
+    let vote_tx = api.vote(fan_tx.pub_key(), 0, &team_pubkey, &fan_key);
+    testkit.create_block();
+    assert_eq!(
+        api.get_transaction_status(&vote_tx.hash()),
+        TransactionStatus::Failure {
+            height: 3,
+            position: 0,
+            code: Some(Error::TeamNotFound as u8),
+            name: Some("TeamNotFound".to_owned()),
+            description: Some("Team doesn't exist".to_owned()),
+        }
+    );
+    let response: serde_json::Value = api.inner.get_err(
+        ApiKind::Service(SERVICE_NAME), &format!("v1/transaction/{}", Hash::zero().to_string())
+    );
+    assert_eq!(response, json!("Transaction not found"));
+}
+
+
+#[test]
+fn test_get_errors() {
+    let (_, api) = create_testkit();
+    let errors: Vec<ErrorInfo> = api.inner.get(ApiKind::Service(SERVICE_NAME), "v1/errors");
+    assert_eq!(errors.len(), 34);
+    assert_eq!(Error::all().last(), Some(&Error::SameTeam));
+    assert_eq!(Error::from_code(33), Some(Error::SameTeam));
+    assert_eq!(Error::from_code(34), None);
+    for (i, error) in errors.iter().enumerate() {
+        assert_eq!(error.code as usize, i);
+    }
+    let already_voted = &errors[Error::AlreadyVoted as usize];
+    assert_eq!(already_voted.name, "AlreadyVoted");
+    assert_eq!(already_voted.description, "Fan has already cast all the votes");
+}
+
+
+#[test]
+fn test_fan_wallet_proof() {
+    let (mut testkit, api) = create_testkit();
+    let (tx, _) = api.create_fan_wallet("Alice");
+    testkit.create_block();
+    let proof = api.get_fan_wallet_proof(tx.pub_key());
+    let to_wallet = proof.to_wallet.check().unwrap();
+    let wallets = to_wallet.entries();
+    assert_eq!(wallets.len(), 1);
+    assert_eq!(wallets[0].0, tx.pub_key());
+    assert_eq!(wallets[0].1.name(), "Alice");
+    let to_table = proof.to_table.check().unwrap();
+    assert_eq!(to_table.merkle_root(), *proof.block_proof.block.state_hash());
+    assert_eq!(
+        to_table.entries(),
+        vec![(
+            &Blockchain::service_table_unique_key(SERVICE_ID, FAN_WALLETS_TABLE),
+            &to_wallet.merkle_root(),
+        )]
+    );
+}
+
+
+#[test]
+fn test_team_wallet_proof() {
+    let (mut testkit, api) = create_testkit();
+    let (team_tx, _) = api.create_team_wallet("Wonderland");
+    testkit.create_block();
+    let proof = api.get_team_wallet_proof(team_tx.pub_key());
+    assert_eq!(proof.block_proof.block.height(), testkit.height());
+    let to_wallet = proof.to_wallet.check().unwrap();
+    let wallets = to_wallet.entries();
+    assert_eq!(wallets.len(), 1);
+    assert_eq!(wallets[0].1.name(), "Wonderland");
+    let to_table = proof.to_table.check().unwrap();
+    assert_eq!(to_table.merkle_root(), *proof.block_proof.block.state_hash());
+    assert_eq!(
+        to_table.entries(),
+        vec![(
+            &Blockchain::service_table_unique_key(SERVICE_ID, TEAM_WALLETS_TABLE),
+            &to_wallet.merkle_root(),
+        )]
+    );
+}
+
+
+#[test]
+fn test_tally_proof() {
+    let (mut testkit, api) = create_testkit();
+    let (fan_tx, fan_key) = api.create_fan_wallet("Alice");
+    let (team_tx, _) = api.create_team_wallet("Wonderland");
+    testkit.create_block();
+    api.create_election("World Cup winner", vec![*team_tx.pub_key()]);
+    testkit.create_block();
+    api.vote(fan_tx.pub_key(), 0, team_tx.pub_key(), &fan_key);
+    testkit.create_block();
+    let proof: TallyProof = serde_json::from_value(api.get_tally_proof(team_tx.pub_key(), 0)).unwrap();
+    let to_tally = proof.to_tally.check().unwrap();
+    let tallies = to_tally.entries();
+    assert_eq!(tallies.len(), 1);
+    assert_eq!(*tallies[0].0, election_key(0, team_tx.pub_key()));
+    assert_eq!(tallies[0].1.votes(), 1);
+    let to_table = proof.to_table.check().unwrap();
+    assert_eq!(to_table.merkle_root(), *proof.block_proof.block.state_hash());
+    assert_eq!(
+        to_table.entries(),
+        vec![(
+            &Blockchain::service_table_unique_key(SERVICE_ID, TALLIES_TABLE),
+            &to_tally.merkle_root(),
+        )]
+    );
+}
+
+
+#[test]
+fn test_missing_wallet_proof() {
+    let (mut testkit, api) = create_testkit();
+    let (pubkey, _) = crypto::gen_keypair();
+    testkit.create_block();
+    let proof = api.get_fan_wallet_proof(&pubkey);
+    let to_wallet = proof.to_wallet.check().unwrap();
+    assert!(to_wallet.entries().is_empty());
+    assert_eq!(to_wallet.missing_keys(), vec![&pubkey]);
+}
+
+
+#[test]
+fn test_client_verifies_tally() {
+    let (mut testkit, api) = create_testkit();
+    let (fan_tx, fan_key) = api.create_fan_wallet("Alice");
+    let (team_tx, _) = api.create_team_wallet("Wonderland");
+    testkit.create_block();
+    api.create_election("World Cup winner", vec![*team_tx.pub_key()]);
+    testkit.create_block();
+    api.vote(fan_tx.pub_key(), 0, team_tx.pub_key(), &fan_key);
+    testkit.create_block();
+    let response = api.get_tally_proof(team_tx.pub_key(), 0);
+    let validators = validator_keys(&testkit);
+    let tally = client::verify_tally(response.clone(), 0, team_tx.pub_key(), &validators).unwrap();
+    assert_eq!(tally.votes(), 1);
+    // The proof doesn't cover the tally in another election
+    assert_matches!(
+        client::verify_tally(response, 1, team_tx.pub_key(), &validators),
+        Err(client::Error::EntryNotFound)
+    );
+}
+
+
+#[test]
+fn test_client_verifies_team_wallet() {
+    let (mut testkit, api) = create_testkit();
+    let (team_tx, _) = api.create_team_wallet("Wonderland");
+    testkit.create_block();
+    let response: serde_json::Value = api.inner.get(
+        ApiKind::Service(SERVICE_NAME),
+        &format!("v1/team/wallet/{}/proof", team_tx.pub_key().to_string()),
+    );
+    let validators = validator_keys(&testkit);
+    let wallet = client::verify_team_wallet(response, team_tx.pub_key(), &validators).unwrap();
+    assert_eq!(wallet.name(), "Wonderland");
+}
+
+
+#[test]
+fn test_client_verifies_fan_wallet() {
+    let (mut testkit, api) = create_testkit();
+    let (tx, _) = api.create_fan_wallet("Alice");
+    testkit.create_block();
+    let response: serde_json::Value = api.inner.get(
+        ApiKind::Service(SERVICE_NAME),
+        &format!("v1/fan/wallet/{}/proof", tx.pub_key().to_string()),
+    );
+    let validators = validator_keys(&testkit);
+    let wallet = client::verify_fan_wallet(response.clone(), tx.pub_key(), &validators).unwrap();
+    assert_eq!(wallet.name(), "Alice");
+    // The same proof doesn't contain any other wallet
+    let (other_pubkey, _) = crypto::gen_keypair();
+    assert_matches!(
+        client::verify_fan_wallet(response, &other_pubkey, &validators),
+        Err(client::Error::EntryNotFound)
+    );
+}
+
+
+#[test]
+fn test_client_rejects_unknown_validators() {
+    let (mut testkit, api) = create_testkit();
+    let (tx, _) = api.create_fan_wallet("Alice");
+    testkit.create_block();
+    let response: serde_json::Value = api.inner.get(
+        ApiKind::Service(SERVICE_NAME),
+        &format!("v1/fan/wallet/{}/proof", tx.pub_key().to_string()),
+    );
+    let (fake_validator, _) = crypto::gen_keypair();
+    assert_matches!(
+        client::verify_fan_wallet(response, tx.pub_key(), &[fake_validator]),
+        Err(client::Error::InvalidBlockProof)
+    );
+}
+
+
+#[test]
+fn test_change_vote() {
+    let (mut testkit, api) = create_testkit();
+    let (fan_tx, fan_key) = api.create_fan_wallet("Alice");
+    let (team1_tx, _) = api.create_team_wallet("Wonderland");
+    let (team2_tx, _) = api.create_team_wallet("Underland");
+    testkit.create_block();
+    api.create_election("World Cup winner", vec![*team1_tx.pub_key(), *team2_tx.pub_key()]);
+    testkit.create_block();
+    let vote_tx = api.vote(fan_tx.pub_key(), 0, team1_tx.pub_key(), &fan_key);
+    testkit.create_block();
+    let change_tx = TxChangeVote::new(
+        fan_tx.pub_key(), 0, team1_tx.pub_key(), team2_tx.pub_key(), 0, &fan_key
+    );
+    let tx_info: serde_json::Value = api.inner.post(
+        ApiKind::Service(SERVICE_NAME), "v1/change_vote", &change_tx
+    );
+    assert_eq!(tx_info, json!({ "tx_hash": change_tx.hash() }));
+    testkit.create_block();
+    let rating = api.get_rating(0);
+    assert_eq!(rating[0].pub_key, *team2_tx.pub_key());
+    assert_eq!(rating[0].votes, 1);
+    assert_eq!(rating[1].votes, 0);
+    let history: Vec<Hash> = api.inner.get(
+        ApiKind::Service(SERVICE_NAME),
+        &format!("v1/fan/wallet/{}/ballot/history?election_id=0", fan_tx.pub_key().to_string()),
+    );
+    assert_eq!(history, vec![vote_tx.hash(), change_tx.hash()]);
+}
+
+
+#[test]
+fn test_client_verifies_voters() {
+    let (mut testkit, api) = create_testkit();
+    let (alice_tx, alice_key) = api.create_fan_wallet("Alice");
+    let (bob_tx, bob_key) = api.create_fan_wallet("Bob");
+    let (team1_tx, _) = api.create_team_wallet("Wonderland");
+    let (team2_tx, _) = api.create_team_wallet("Underland");
+    testkit.create_block();
+    api.create_election("World Cup winner", vec![*team1_tx.pub_key(), *team2_tx.pub_key()]);
+    testkit.create_block();
+    api.vote(alice_tx.pub_key(), 0, team1_tx.pub_key(), &alice_key);
+    testkit.create_block();
+    api.vote(bob_tx.pub_key(), 0, team1_tx.pub_key(), &bob_key);
+    testkit.create_block();
+    let change_tx = TxChangeVote::new(
+        bob_tx.pub_key(), 0, team1_tx.pub_key(), team2_tx.pub_key(), 0, &bob_key
+    );
+    testkit.create_block_with_transactions(txvec![change_tx.clone()]);
+    let validators = validator_keys(&testkit);
+
+    let response: serde_json::Value = api.inner.get(
+        ApiKind::Service(SERVICE_NAME),
+        &format!("v1/team/wallet/{}/voters?election_id=0", team1_tx.pub_key().to_string()),
+    );
+    let (tally, voters) = client::verify_voters(response, 0, team1_tx.pub_key(), &validators).unwrap();
+    assert_eq!(tally.votes(), 1);
+    assert_eq!(voters.len(), 3);
+    assert_eq!(voters[2].fan(), bob_tx.pub_key());
+    assert_eq!(*voters[2].tx_hash(), change_tx.hash());
+    assert_eq!(voters[2].votes(), -1);
+    // Voters sum up to the tally
+    assert_eq!(voters.iter().map(|voter| voter.votes()).sum::<i64>(), tally.votes() as i64);
+
+    let response: serde_json::Value = api.inner.get(
+        ApiKind::Service(SERVICE_NAME),
+        &format!("v1/team/wallet/{}/voters?election_id=0&from=1&limit=1",
+                 team1_tx.pub_key().to_string()),
+    );
+    let (_, voters) = client::verify_voters(response, 0, team1_tx.pub_key(), &validators).unwrap();
+    assert_eq!(voters.len(), 1);
+    assert_eq!(voters[0].fan(), bob_tx.pub_key());
+
+    let response: serde_json::Value = api.inner.get(
+        ApiKind::Service(SERVICE_NAME),
+        &format!("v1/team/wallet/{}/voters?election_id=0&from=3", team1_tx.pub_key().to_string()),
+    );
+    let (_, voters) = client::verify_voters(response, 0, team1_tx.pub_key(), &validators).unwrap();
+    assert!(voters.is_empty());
+}
+
+
+#[test]
+fn test_client_verifies_votes_log() {
+    let (mut testkit, api) = create_testkit();
+    let (alice_tx, alice_key) = api.create_fan_wallet("Alice");
+    let (bob_tx, bob_key) = api.create_fan_wallet("Bob");
+    let (team_tx, _) = api.create_team_wallet("Wonderland");
+    testkit.create_block();
+    api.create_election("World Cup winner", vec![*team_tx.pub_key()]);
+    testkit.create_block();
+    let alice_vote = api.vote(alice_tx.pub_key(), 0, team_tx.pub_key(), &alice_key);
+    testkit.create_block();
+    let bob_vote = api.vote(bob_tx.pub_key(), 0, team_tx.pub_key(), &bob_key);
+    testkit.create_block();
+    let validators = validator_keys(&testkit);
+
+    let response: serde_json::Value = api.inner.get(ApiKind::Service(SERVICE_NAME), "v1/votes/log");
+    let votes = client::verify_votes_log(response, &validators).unwrap();
+    assert_eq!(votes, vec![(0, alice_vote.hash()), (1, bob_vote.hash())]);
+
+    let response: serde_json::Value = api.inner.get(
+        ApiKind::Service(SERVICE_NAME), "v1/votes/log?from=1&limit=5"
+    );
+    let votes = client::verify_votes_log(response, &validators).unwrap();
+    assert_eq!(votes, vec![(1, bob_vote.hash())]);
+}
+
+
+#[test]
+fn test_ranked_rating() {
+    let (mut testkit, api) = create_testkit();
+    let fans: Vec<_> = ["Alice", "Bob", "Carol"].iter().map(|name| api.create_fan_wallet(name)).collect();
+    let teams: Vec<_> = ["Wonderland", "Underland", "Overland"].iter()
+        .map(|name| *api.create_team_wallet(name).0.pub_key())
+        .collect();
+    testkit.create_block();
+    let (pubkey, key) = crypto::gen_keypair();
+    testkit.create_block_with_transactions(txvec![
+        TxCreateElection::new(&pubkey, "Top 3", teams.clone(), 0, u64::max_value(), 1,
+                              BallotMode::Ranked as u8, 0, 0, &key),
+    ]);
+    let rankings = vec![
+        vec![teams[0], teams[1]],
+        vec![teams[1], teams[0]],
+        vec![teams[2], teams[1]],
+    ];
+    for (&(ref fan_tx, ref fan_key), ranking) in fans.iter().zip(rankings) {
+        let tx = TxRankedVote::new(fan_tx.pub_key(), 0, ranking, 0, fan_key);
+        let tx_info: serde_json::Value = api.inner.post(
+            ApiKind::Service(SERVICE_NAME), "v1/ranked_vote", &tx
+        );
+        assert_eq!(tx_info, json!({ "tx_hash": tx.hash() }));
+    }
+    testkit.create_block();
+
+    let rating = |method: &str| -> Vec<(PublicKey, u64)> {
+        let rating: Vec<TeamRating> = api.inner.get(
+            ApiKind::Service(SERVICE_NAME),
+            &format!("v1/rating?election_id=0&method={}", method),
+        );
+        rating.into_iter().map(|team| (team.pub_key, team.votes)).collect()
+    };
+    // Teams with equal votes are ordered by public key
+    let mut tied = teams.clone();
+    tied.sort_by(|l, r| l.as_ref().cmp(r.as_ref()));
+    assert_eq!(rating("plurality"), tied.into_iter().map(|team| (team, 1)).collect::<Vec<_>>());
+    assert_eq!(rating("borda"), vec![(teams[1], 4), (teams[0], 3), (teams[2], 2)]);
+    // Overland is eliminated and its voter prefers Underland
+    assert_eq!(rating("irv"), vec![(teams[1], 2), (teams[0], 1), (teams[2], 1)]);
+}
+
+
+#[test]
+fn test_ranked_rating_of_plurality_election() {
+    let (mut testkit, api) = create_testkit();
+    let (team_tx, _) = api.create_team_wallet("Wonderland");
+    testkit.create_block();
+    api.create_election("World Cup winner", vec![*team_tx.pub_key()]);
+    testkit.create_block();
+    let error: serde_json::Value = api.inner.get_err(
+        ApiKind::Service(SERVICE_NAME), "v1/rating?election_id=0&method=borda"
+    );
+    assert!(error["debug"].as_str().unwrap().contains("Election doesn't have ranked ballots"));
+    let error: serde_json::Value = api.inner.get_err(
+        ApiKind::Service(SERVICE_NAME), "v1/rating?election_id=0&method=foo"
+    );
+    assert!(error["debug"].as_str().unwrap().contains("Invalid request param: `method`"));
+}
+
+
+#[test]
+fn test_get_unrevealed() {
+    let (mut testkit, api) = create_testkit();
+    let (fan_tx, fan_key) = api.create_fan_wallet("Alice");
+    let (team_tx, _) = api.create_team_wallet("Wonderland");
+    testkit.create_block();
+    let (pubkey, key) = crypto::gen_keypair();
+    let commitment = vote_commitment(team_tx.pub_key(), &crypto::hash(b"salt"));
+    testkit.create_block_with_transactions(txvec![
+        TxCreateElection::new(&pubkey, "World Cup winner", vec![*team_tx.pub_key()], 0, 100, 1,
+                              BallotMode::Single as u8, 50, 0, &key),
+        TxCommitVote::new(fan_tx.pub_key(), 0, &commitment, 0, &fan_key),
+    ]);
+    let unrevealed: Vec<PublicKey> = api.inner.get(
+        ApiKind::Service(SERVICE_NAME), "v1/election/0/unrevealed"
+    );
+    assert_eq!(unrevealed, vec![*fan_tx.pub_key()]);
+    let stored: serde_json::Value = api.inner.get(
+        ApiKind::Service(SERVICE_NAME),
+        &format!("v1/fan/wallet/{}/commitment?election_id=0", fan_tx.pub_key().to_string()),
+    );
+    assert_eq!(stored["commitment"], json!(commitment));
+    assert_eq!(stored["revealed"], json!(false));
+}
+
+
+#[test]
+fn test_export_to_new_network() {
+    let (mut testkit, api) = create_testkit();
+    let (alice_tx, alice_key) = api.create_fan_wallet("Alice");
+    let (bob_tx, bob_key) = api.create_fan_wallet("Bob");
+    let (carol_tx, carol_key) = api.create_fan_wallet("Carol");
+    let teams: Vec<_> = ["Wonderland", "Underland"].iter()
+        .map(|name| *api.create_team_wallet(name).0.pub_key())
+        .collect();
+    testkit.create_block();
+    let (author_pubkey, author_key) = crypto::gen_keypair();
+    let commitment = vote_commitment(&teams[0], &crypto::hash(b"salt"));
+    testkit.create_block_with_transactions(txvec![
+        TxCreateElection::new(&author_pubkey, "World Cup winner", teams.clone(), 0, 100, 1,
+                              BallotMode::Single as u8, 0, 0, &author_key),
+        TxCreateElection::new(&author_pubkey, "Top 2", teams.clone(), 0, 100, 1,
+                              BallotMode::Ranked as u8, 0, 0, &author_key),
+        TxCreateElection::new(&author_pubkey, "Best goal", teams.clone(), 0, 100, 1,
+                              BallotMode::Single as u8, 50, 0, &author_key),
+        TxDelegate::new(alice_tx.pub_key(), bob_tx.pub_key(), 0, &alice_key),
+        TxVote::new(bob_tx.pub_key(), 0, &teams[0], 0, &bob_key),
+        TxVote::new(carol_tx.pub_key(), 0, &teams[1], 0, &carol_key),
+        TxRankedVote::new(carol_tx.pub_key(), 1, vec![teams[1], teams[0]], 0, &carol_key),
+        TxCommitVote::new(carol_tx.pub_key(), 2, &commitment, 0, &carol_key),
+    ]);
+
+    let config: VotesConfig = api.inner.get(ApiKind::Service(SERVICE_NAME), "v1/export");
+    assert_eq!(config.fans.len(), 3);
+    assert_eq!(config.elections.len(), 3);
+    let mut new_testkit = TestKitBuilder::validator()
+        .with_service(VotesService::new(config))
+        .create();
+    let block = new_testkit.create_block_with_transactions(txvec![
+        TxVote::new(alice_tx.pub_key(), 0, &teams[1], 1, &alice_key),
+    ]);
+    assert_eq!(
+        block.transactions[0].status().unwrap_err().description(),
+        Some("Vote is already cast by the delegate")
+    );
+
+    let snapshot = new_testkit.snapshot();
+    let schema = VotesSchema::new(&snapshot);
+    assert!(schema.is_admin(&admin_keypair().0));
+    assert_eq!(schema.team_wallet(&teams[0]).unwrap().name(), "Wonderland");
+    assert_eq!(schema.fan_wallet(alice_tx.pub_key()).unwrap().delegate(), bob_tx.pub_key());
+    assert_eq!(schema.fan_wallet(bob_tx.pub_key()).unwrap().weight(), 2);
+    assert_eq!(schema.ballot(0, bob_tx.pub_key()).unwrap().weight(), 2);
+    assert_eq!(schema.tally(0, &teams[0]).unwrap().votes(), 2);
+    assert_eq!(schema.tally(0, &teams[1]).unwrap().votes(), 1);
+    assert!(schema.represented_fans(0).contains(alice_tx.pub_key()));
+    assert_eq!(schema.election_rankings(1), vec![(vec![teams[1], teams[0]], 1)]);
+    assert_eq!(schema.tally(1, &teams[1]).unwrap().votes(), 1);
+    assert_eq!(schema.unrevealed_fans(2), vec![*carol_tx.pub_key()]);
+    assert_eq!(*schema.commitment(2, carol_tx.pub_key()).unwrap().commitment(), commitment);
+}
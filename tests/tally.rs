@@ -0,0 +1,66 @@
+extern crate exonum;
+extern crate football_voting;
+
+
+use exonum::crypto::{self, PublicKey};
+use football_voting::tally::{borda, instant_runoff};
+
+
+fn teams(n: usize) -> Vec<PublicKey> {
+    (0..n).map(|_| crypto::gen_keypair().0).collect()
+}
+
+
+fn unweighted(rankings: Vec<Vec<PublicKey>>) -> Vec<(Vec<PublicKey>, u64)> {
+    rankings.into_iter().map(|ranking| (ranking, 1)).collect()
+}
+
+
+#[test]
+fn test_borda() {
+    let t = teams(3);
+    let rankings = unweighted(vec![
+        vec![t[0], t[1], t[2]],
+        vec![t[1], t[0]],
+        vec![t[1]],
+    ]);
+    assert_eq!(borda(&t, &rankings), vec![(t[1], 5), (t[0], 3), (t[2], 0)]);
+}
+
+
+#[test]
+fn test_instant_runoff() {
+    let t = teams(3);
+    // t[0] leads first preferences, but the voters of eliminated t[2] prefer t[1]
+    let rankings = unweighted(vec![
+        vec![t[0]], vec![t[0]], vec![t[0]],
+        vec![t[1]], vec![t[1]],
+        vec![t[2], t[1]], vec![t[2], t[1]],
+    ]);
+    assert_eq!(instant_runoff(&t, &rankings), vec![(t[1], 4), (t[0], 3), (t[2], 2)]);
+}
+
+
+#[test]
+fn test_instant_runoff_majority() {
+    let t = teams(3);
+    let rankings = unweighted(vec![vec![t[2]], vec![t[2], t[0]], vec![t[1]]]);
+    assert_eq!(instant_runoff(&t, &rankings), vec![(t[2], 2), (t[1], 1), (t[0], 0)]);
+}
+
+
+#[test]
+fn test_weighted_rankings() {
+    let t = teams(3);
+    // The second ranking carries the votes of four fans
+    let rankings = vec![(vec![t[0], t[1]], 1), (vec![t[2], t[1]], 4), (vec![t[1]], 2)];
+    assert_eq!(borda(&t, &rankings), vec![(t[1], 9), (t[2], 8), (t[0], 2)]);
+    assert_eq!(instant_runoff(&t, &rankings), vec![(t[2], 4), (t[1], 2), (t[0], 1)]);
+}
+
+
+#[test]
+fn test_instant_runoff_without_rankings() {
+    let t = teams(2);
+    assert_eq!(instant_runoff(&t, &[]), vec![(t[0], 0), (t[1], 0)]);
+}
@@ -3,30 +3,68 @@ extern crate football_voting;
 #[macro_use] extern crate exonum_testkit;
 
 
-use exonum::blockchain::Transaction;
-use exonum::crypto::{self, PublicKey, SecretKey, Hash};
+use exonum::blockchain::{Blockchain, Schema, Transaction};
+use exonum::crypto::{self, PublicKey, SecretKey, Hash, CryptoHash, Seed};
 use exonum::explorer::CommittedTransaction;
-use exonum::blockchain::TransactionError;
+use exonum::blockchain::{TransactionError, TransactionErrorType};
 use exonum_testkit::{TestKit, TestKitBuilder};
-use football_voting::schema::{VotesSchema};
-use football_voting::transactions::{TxCreateWallet, TxVote};
+use football_voting::config::{ElectionConfig, TeamConfig, VotesConfig};
+use football_voting::schema::{election_key, parse_leaderboard_key, VotesSchema};
+use football_voting::transactions::{TxCreateWallet, TxVote, TxCreateElection, TxRegisterTeam,
+                                    TxChangeVote, TxRevokeVote, TxMultiVote, TxRankedVote,
+                                    TxCommitVote, TxRevealVote, TxDelegate, TxUndelegate};
+use football_voting::election::{vote_commitment, BallotMode, TeamVotes};
 use football_voting::service::VotesService;
 use football_voting::errors::Error;
+use football_voting::constants::{SERVICE_ID, HISTORY_ROOTS_TABLE, TALLIES_TABLE, VOTES_LOG_TABLE};
+
+
+fn admin_keypair() -> (PublicKey, SecretKey) {
+    crypto::gen_keypair_from_seed(&Seed::new([1; 32]))
+}
 
 
 fn init_testkit() -> TestKit {
+    let (admin_pubkey, _) = admin_keypair();
+    init_testkit_with_config(VotesConfig {
+        admins: vec![admin_pubkey],
+        ..Default::default()
+    })
+}
+
+
+fn init_testkit_with_config(config: VotesConfig) -> TestKit {
     TestKitBuilder::validator()
-        .with_service(VotesService)
+        .with_service(VotesService::new(config))
         .create()
 }
 
 
+fn register_team(pubkey: &PublicKey, name: &str) -> TxRegisterTeam {
+    let (admin_pubkey, admin_key) = admin_keypair();
+    TxRegisterTeam::new(&admin_pubkey, pubkey, name, 0, &admin_key)
+}
+
+
+fn create_election(candidates: Vec<PublicKey>) -> TxCreateElection {
+    create_election_with_window(candidates, 0, u64::max_value())
+}
+
+
+fn create_election_with_window(candidates: Vec<PublicKey>, start_height: u64, end_height: u64)
+                               -> TxCreateElection {
+    let (pubkey, key) = crypto::gen_keypair();
+    TxCreateElection::new(&pubkey, "World Cup winner", candidates, start_height, end_height, 1,
+                          BallotMode::Single as u8, 0, 0, &key)
+}
+
+
 #[test]
 fn test_create_fan_wallet() {
     let mut testkit = init_testkit();
     let (pubkey, key) = crypto::gen_keypair();
     testkit.create_block_with_transactions(txvec![
-        TxCreateWallet::new(&pubkey, "Alice", false, &key),
+        TxCreateWallet::new(&pubkey, "Alice", &key),
     ]);
     let wallet = {
         let snapshot = testkit.snapshot();
@@ -36,8 +74,8 @@ fn test_create_fan_wallet() {
     };
     assert_eq!(*wallet.pub_key(), pubkey);
     assert_eq!(wallet.name(), "Alice");
-    assert_eq!(wallet.voted(), false);
-    assert_eq!(wallet.vote_hash(), &Hash::zero().to_hex());
+    assert!(!wallet.has_delegate());
+    assert_eq!(wallet.weight(), 1);
 }
 
 
@@ -47,10 +85,10 @@ fn test_create_duplicate_fan_wallet() {
     let mut testkit = init_testkit();
     let (pubkey, key) = crypto::gen_keypair();
     testkit.create_block_with_transactions(txvec![
-        TxCreateWallet::new(&pubkey, "Alice", false, &key),
+        TxCreateWallet::new(&pubkey, "Alice", &key),
     ]);
     let block = testkit.create_block_with_transactions(txvec![
-        TxCreateWallet::new(&pubkey, "Alice", false, &key),
+        TxCreateWallet::new(&pubkey, "Alice", &key),
     ]);
     block.transactions[0].status();
 }
@@ -60,9 +98,9 @@ fn test_create_duplicate_fan_wallet() {
 #[test]
 fn test_create_team_wallet() {
     let mut testkit = init_testkit();
-    let (pubkey, key) = crypto::gen_keypair();
+    let (pubkey, _) = crypto::gen_keypair();
     testkit.create_block_with_transactions(txvec![
-        TxCreateWallet::new(&pubkey, "Wonderland", true, &key),
+        register_team(&pubkey, "Wonderland"),
     ]);
     let wallet = {
         let snapshot = testkit.snapshot();
@@ -72,7 +110,6 @@ fn test_create_team_wallet() {
     };
     assert_eq!(*wallet.pub_key(), pubkey);
     assert_eq!(wallet.name(), "Wonderland");
-    assert_eq!(wallet.votes(), 0);
 }
 
 
@@ -80,108 +117,1442 @@ fn test_create_team_wallet() {
 #[should_panic]
 fn test_create_duplicate_team_wallet() {
     let mut testkit = init_testkit();
-    let (pubkey, key) = crypto::gen_keypair();
+    let (pubkey, _) = crypto::gen_keypair();
     testkit.create_block_with_transactions(txvec![
-        TxCreateWallet::new(&pubkey, "Wonderland", true, &key),
+        register_team(&pubkey, "Wonderland"),
     ]);
     let block = testkit.create_block_with_transactions(txvec![
-        TxCreateWallet::new(&pubkey, "Wonderland", true, &key),
+        register_team(&pubkey, "Wonderland"),
     ]);
     block.transactions[0].status();
 }
 
 
 #[test]
-fn test_vote() {
+fn test_duplicate_wallet_errors() {
+    let mut testkit = init_testkit();
+    let (alice_pubkey, alice_key) = crypto::gen_keypair();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    testkit.create_block_with_transactions(txvec![
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        register_team(&wonderland_pubkey, "Wonderland"),
+    ]);
+    let block = testkit.create_block_with_transactions(txvec![
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        register_team(&wonderland_pubkey, "Wonderland"),
+    ]);
+    let fan_error = block.transactions[0].status().unwrap_err();
+    assert_eq!(fan_error.error_type(), TransactionErrorType::Code(Error::FanAlreadyExists as u8));
+    assert_eq!(fan_error.description(), Some("Fan already exists"));
+    let team_error = block.transactions[1].status().unwrap_err();
+    assert_eq!(team_error.error_type(), TransactionErrorType::Code(Error::TeamAlreadyExists as u8));
+    assert_eq!(team_error.description(), Some("Team already exists"));
+}
+
+
+#[test]
+fn test_key_registered_as_other_kind() {
     let mut testkit = init_testkit();
     let (alice_pubkey, alice_key) = crypto::gen_keypair();
     let (wonderland_pubkey, wonderland_key) = crypto::gen_keypair();
+    let block = testkit.create_block_with_transactions(txvec![
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        register_team(&wonderland_pubkey, "Wonderland"),
+        register_team(&alice_pubkey, "Alice FC"),
+        TxCreateWallet::new(&wonderland_pubkey, "Wonderland fan", &wonderland_key),
+    ]);
+    for tx in &block.transactions[2..4] {
+        assert_eq!(
+            tx.status().unwrap_err().description(),
+            Some("Key is registered as a wallet of the other kind")
+        );
+    }
+    let snapshot = testkit.snapshot();
+    let schema = VotesSchema::new(&snapshot);
+    assert!(schema.team_wallet(&alice_pubkey).is_none());
+    assert!(schema.fan_wallet(&wonderland_pubkey).is_none());
+}
+
+
+#[test]
+fn test_register_team_by_non_admin() {
+    let mut testkit = init_testkit();
+    let (pubkey, key) = crypto::gen_keypair();
+    let block = testkit.create_block_with_transactions(txvec![
+        TxRegisterTeam::new(&pubkey, &pubkey, "Brazil", 0, &key),
+    ]);
+    assert_eq!(
+        block.transactions[0].status().unwrap_err().description(),
+        Some("Transaction author is not an administrator")
+    );
+    let snapshot = testkit.snapshot();
+    assert!(VotesSchema::new(&snapshot).team_wallet(&pubkey).is_none());
+}
+
+
+#[test]
+fn test_create_election() {
+    let mut testkit = init_testkit();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    let (underland_pubkey, _) = crypto::gen_keypair();
+    testkit.create_block_with_transactions(txvec![
+        register_team(&wonderland_pubkey, "Wonderland"),
+        register_team(&underland_pubkey, "Underland"),
+        create_election(vec![wonderland_pubkey, underland_pubkey]),
+        create_election(vec![underland_pubkey]),
+    ]);
+    let snapshot = testkit.snapshot();
+    let schema = VotesSchema::new(&snapshot);
+    let election = schema.election(0).expect("No election persisted");
+    assert_eq!(election.id(), 0);
+    assert_eq!(election.title(), "World Cup winner");
+    assert_eq!(election.candidates(), vec![wonderland_pubkey, underland_pubkey]);
+    assert_eq!(schema.election(1).unwrap().candidates(), vec![underland_pubkey]);
+    assert_eq!(schema.tally(0, &wonderland_pubkey).unwrap().votes(), 0);
+    assert!(schema.tally(1, &wonderland_pubkey).is_none());
+}
+
+
+#[test]
+fn test_create_election_with_unknown_candidate() {
+    let mut testkit = init_testkit();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    let block = testkit.create_block_with_transactions(txvec![
+        create_election(vec![wonderland_pubkey]),
+    ]);
+    assert_eq!(
+        block.transactions[0].status().unwrap_err().description(),
+        Some("Team doesn't exist")
+    );
+    let snapshot = testkit.snapshot();
+    assert!(VotesSchema::new(&snapshot).election(0).is_none());
+}
+
+
+#[test]
+fn test_create_election_with_duplicate_candidate() {
+    let mut testkit = init_testkit();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    let block = testkit.create_block_with_transactions(txvec![
+        register_team(&wonderland_pubkey, "Wonderland"),
+        create_election(vec![wonderland_pubkey, wonderland_pubkey]),
+    ]);
+    assert_eq!(
+        block.transactions[1].status().unwrap_err().description(),
+        Some("Team is listed as a candidate more than once")
+    );
+}
+
+
+#[test]
+fn test_create_election_with_empty_window() {
+    let mut testkit = init_testkit();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    let block = testkit.create_block_with_transactions(txvec![
+        register_team(&wonderland_pubkey, "Wonderland"),
+        create_election_with_window(vec![wonderland_pubkey], 5, 5),
+    ]);
+    assert_eq!(
+        block.transactions[1].status().unwrap_err().description(),
+        Some("Voting window is empty")
+    );
+}
+
+
+#[test]
+fn test_vote() {
+    let mut testkit = init_testkit();
+    let (alice_pubkey, alice_key) = crypto::gen_keypair();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    let vote = TxVote::new(&alice_pubkey, 0, &wonderland_pubkey, 0, &alice_key);
     testkit.create_block_with_transactions(txvec![
-        TxCreateWallet::new(&alice_pubkey, "Alice", false, &alice_key),
-        TxCreateWallet::new(&wonderland_pubkey, "Wonderland", true, &wonderland_key),
-        TxVote::new(&alice_pubkey, &wonderland_pubkey, 0, &alice_key),
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        register_team(&wonderland_pubkey, "Wonderland"),
+        create_election(vec![wonderland_pubkey]),
+        vote.clone(),
     ]);
-    let wallets = {
+    let snapshot = testkit.snapshot();
+    let schema = VotesSchema::new(&snapshot);
+    let ballot = schema.ballot(0, &alice_pubkey).expect("Ballot not persisted");
+    assert_eq!(ballot.voted(), true);
+    assert_eq!(*ballot.vote_hash(), vote.hash());
+    assert_eq!(schema.tally(0, &wonderland_pubkey).unwrap().votes(), 1);
+}
+
+
+#[test]
+fn test_multiple_vote() {
+    let mut testkit = init_testkit();
+    let (alice_pubkey, alice_key) = crypto::gen_keypair();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    testkit.create_block_with_transactions(txvec![
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        register_team(&wonderland_pubkey, "Wonderland"),
+        create_election(vec![wonderland_pubkey]),
+        TxVote::new(&alice_pubkey, 0, &wonderland_pubkey, 0, &alice_key),
+        TxVote::new(&alice_pubkey, 0, &wonderland_pubkey, 1, &alice_key),
+    ]);
+    let snapshot = testkit.snapshot();
+    let schema = VotesSchema::new(&snapshot);
+    assert_eq!(schema.tally(0, &wonderland_pubkey).unwrap().votes(), 1);
+}
+
+
+#[test]
+fn test_vote_in_several_elections() {
+    let mut testkit = init_testkit();
+    let (alice_pubkey, alice_key) = crypto::gen_keypair();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    let (underland_pubkey, _) = crypto::gen_keypair();
+    testkit.create_block_with_transactions(txvec![
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        register_team(&wonderland_pubkey, "Wonderland"),
+        register_team(&underland_pubkey, "Underland"),
+        create_election(vec![wonderland_pubkey, underland_pubkey]),
+        create_election(vec![wonderland_pubkey, underland_pubkey]),
+        TxVote::new(&alice_pubkey, 0, &wonderland_pubkey, 0, &alice_key),
+        TxVote::new(&alice_pubkey, 1, &underland_pubkey, 0, &alice_key),
+    ]);
+    let snapshot = testkit.snapshot();
+    let schema = VotesSchema::new(&snapshot);
+    assert_eq!(schema.tally(0, &wonderland_pubkey).unwrap().votes(), 1);
+    assert_eq!(schema.tally(0, &underland_pubkey).unwrap().votes(), 0);
+    assert_eq!(schema.tally(1, &wonderland_pubkey).unwrap().votes(), 0);
+    assert_eq!(schema.tally(1, &underland_pubkey).unwrap().votes(), 1);
+}
+
+
+#[test]
+fn test_vote_in_non_existing_election() {
+    let mut testkit = init_testkit();
+    let (alice_pubkey, alice_key) = crypto::gen_keypair();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    let block = testkit.create_block_with_transactions(txvec![
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        register_team(&wonderland_pubkey, "Wonderland"),
+        TxVote::new(&alice_pubkey, 0, &wonderland_pubkey, 0, &alice_key),
+    ]);
+    assert_eq!(
+        block.transactions[2].status().unwrap_err().description(),
+        Some("Election doesn't exist")
+    );
+}
+
+
+#[test]
+fn test_vote_for_non_candidate() {
+    let mut testkit = init_testkit();
+    let (alice_pubkey, alice_key) = crypto::gen_keypair();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    let (underland_pubkey, _) = crypto::gen_keypair();
+    let block = testkit.create_block_with_transactions(txvec![
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        register_team(&wonderland_pubkey, "Wonderland"),
+        register_team(&underland_pubkey, "Underland"),
+        create_election(vec![wonderland_pubkey]),
+        TxVote::new(&alice_pubkey, 0, &underland_pubkey, 0, &alice_key),
+    ]);
+    assert_eq!(
+        block.transactions[4].status().unwrap_err().description(),
+        Some("Team is not a candidate in the election")
+    );
+    let snapshot = testkit.snapshot();
+    assert!(VotesSchema::new(&snapshot).ballot(0, &alice_pubkey).is_none());
+}
+
+
+#[test]
+fn test_vote_for_non_existing_team() {
+    let mut testkit = init_testkit();
+    let (alice_pubkey, alice_key) = crypto::gen_keypair();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    let (underland_pubkey, _) = crypto::gen_keypair();
+    testkit.create_block_with_transactions(txvec![
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        register_team(&wonderland_pubkey, "Wonderland"),
+        create_election(vec![wonderland_pubkey]),
+        TxVote::new(&alice_pubkey, 0, &underland_pubkey, 0, &alice_key),
+    ]);
+    let snapshot = testkit.snapshot();
+    let schema = VotesSchema::new(&snapshot);
+    assert!(schema.ballot(0, &alice_pubkey).is_none());
+    assert_eq!(schema.tally(0, &wonderland_pubkey).unwrap().votes(), 0);
+}
+
+
+#[test]
+fn test_vote_as_non_existing_fan() {
+    let mut testkit = init_testkit();
+    let (alice_pubkey, alice_key) = crypto::gen_keypair();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    testkit.create_block_with_transactions(txvec![
+        register_team(&wonderland_pubkey, "Wonderland"),
+        create_election(vec![wonderland_pubkey]),
+        TxVote::new(&alice_pubkey, 0, &wonderland_pubkey, 0, &alice_key),
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+    ]);
+    let snapshot = testkit.snapshot();
+    let schema = VotesSchema::new(&snapshot);
+    assert!(schema.ballot(0, &alice_pubkey).is_none());
+    assert_eq!(schema.tally(0, &wonderland_pubkey).unwrap().votes(), 0);
+}
+
+
+#[test]
+fn test_state_hash_changes_after_vote() {
+    let mut testkit = init_testkit();
+    let (alice_pubkey, alice_key) = crypto::gen_keypair();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    testkit.create_block_with_transactions(txvec![
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        register_team(&wonderland_pubkey, "Wonderland"),
+        create_election(vec![wonderland_pubkey]),
+    ]);
+    let state_hash_before = VotesSchema::new(&testkit.snapshot()).state_hash();
+    let block = testkit.create_block_with_transactions(txvec![
+        TxVote::new(&alice_pubkey, 0, &wonderland_pubkey, 0, &alice_key),
+    ]);
+    let state_hash_after = VotesSchema::new(&testkit.snapshot()).state_hash();
+    assert_eq!(state_hash_after.len(), state_hash_before.len());
+    assert_ne!(state_hash_before[TALLIES_TABLE], state_hash_after[TALLIES_TABLE]);
+
+    let snapshot = testkit.snapshot();
+    let to_table = Schema::new(&snapshot).get_proof_to_service_table(SERVICE_ID, TALLIES_TABLE);
+    let checked = to_table.check().unwrap();
+    assert_eq!(checked.merkle_root(), *block.header.state_hash());
+    assert_eq!(
+        checked.entries(),
+        vec![(
+            &Blockchain::service_table_unique_key(SERVICE_ID, TALLIES_TABLE),
+            &state_hash_after[TALLIES_TABLE],
+        )]
+    );
+}
+
+
+#[test]
+fn test_vote_outside_window() {
+    let mut testkit = init_testkit();
+    let (alice_pubkey, alice_key) = crypto::gen_keypair();
+    let (bob_pubkey, bob_key) = crypto::gen_keypair();
+    let (carol_pubkey, carol_key) = crypto::gen_keypair();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    // Voting is open only in the block #3
+    testkit.create_block_with_transactions(txvec![
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        TxCreateWallet::new(&bob_pubkey, "Bob", &bob_key),
+        TxCreateWallet::new(&carol_pubkey, "Carol", &carol_key),
+        register_team(&wonderland_pubkey, "Wonderland"),
+        create_election_with_window(vec![wonderland_pubkey], 3, 4),
+    ]);
+    let early = testkit.create_block_with_transactions(txvec![
+        TxVote::new(&alice_pubkey, 0, &wonderland_pubkey, 0, &alice_key),
+    ]);
+    assert_eq!(
+        early.transactions[0].status().unwrap_err().description(),
+        Some("Voting has not started yet")
+    );
+    let open = testkit.create_block_with_transactions(txvec![
+        TxVote::new(&bob_pubkey, 0, &wonderland_pubkey, 0, &bob_key),
+    ]);
+    assert!(open.transactions[0].status().is_ok());
+    let late = testkit.create_block_with_transactions(txvec![
+        TxVote::new(&carol_pubkey, 0, &wonderland_pubkey, 0, &carol_key),
+    ]);
+    assert_eq!(
+        late.transactions[0].status().unwrap_err().description(),
+        Some("Voting is already closed")
+    );
+    let snapshot = testkit.snapshot();
+    assert_eq!(VotesSchema::new(&snapshot).tally(0, &wonderland_pubkey).unwrap().votes(), 1);
+}
+
+
+#[test]
+fn test_genesis_config() {
+    let (admin_pubkey, _) = admin_keypair();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    let (underland_pubkey, _) = crypto::gen_keypair();
+    let testkit = init_testkit_with_config(VotesConfig {
+        admins: vec![admin_pubkey],
+        teams: vec![
+            TeamConfig { pub_key: wonderland_pubkey, name: "Wonderland".to_owned() },
+            TeamConfig { pub_key: underland_pubkey, name: "Underland".to_owned() },
+        ],
+        election: Some(ElectionConfig {
+            title: "World Cup winner".to_owned(),
+            start_height: 1,
+            end_height: 100,
+            max_votes_per_fan: 1,
+            ballot_mode: BallotMode::Single,
+            reveal_height: 0,
+        }),
+        ..Default::default()
+    });
+    let snapshot = testkit.snapshot();
+    let schema = VotesSchema::new(&snapshot);
+    assert!(schema.is_admin(&admin_pubkey));
+    assert_eq!(schema.team_wallet(&wonderland_pubkey).unwrap().name(), "Wonderland");
+    assert_eq!(schema.team_wallet(&underland_pubkey).unwrap().name(), "Underland");
+    let election = schema.election(0).expect("Genesis election not persisted");
+    assert_eq!(election.title(), "World Cup winner");
+    assert_eq!(election.candidates(), vec![wonderland_pubkey, underland_pubkey]);
+    assert_eq!(election.start_height(), 1);
+    assert_eq!(election.end_height(), 100);
+    assert_eq!(election.max_votes(), 1);
+    assert_eq!(schema.tally(0, &underland_pubkey).unwrap().votes(), 0);
+}
+
+
+#[test]
+#[should_panic(expected = "Invalid genesis election: Voting window is empty")]
+fn test_invalid_genesis_config() {
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    init_testkit_with_config(VotesConfig {
+        admins: vec![],
+        teams: vec![TeamConfig { pub_key: wonderland_pubkey, name: "Wonderland".to_owned() }],
+        election: Some(ElectionConfig {
+            title: "World Cup winner".to_owned(),
+            start_height: 1,
+            end_height: 100,
+            max_votes_per_fan: 1,
+            ballot_mode: BallotMode::Points,
+            reveal_height: 100,
+        }),
+        ..Default::default()
+    });
+}
+
+
+#[test]
+fn test_several_votes_per_fan() {
+    let mut testkit = init_testkit();
+    let (alice_pubkey, alice_key) = crypto::gen_keypair();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    let (underland_pubkey, _) = crypto::gen_keypair();
+    let (author_pubkey, author_key) = crypto::gen_keypair();
+    let block = testkit.create_block_with_transactions(txvec![
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        register_team(&wonderland_pubkey, "Wonderland"),
+        register_team(&underland_pubkey, "Underland"),
+        TxCreateElection::new(&author_pubkey, "Best goal", vec![wonderland_pubkey, underland_pubkey],
+                              0, u64::max_value(), 2, BallotMode::Single as u8, 0, 0, &author_key),
+        TxVote::new(&alice_pubkey, 0, &wonderland_pubkey, 0, &alice_key),
+        TxVote::new(&alice_pubkey, 0, &underland_pubkey, 0, &alice_key),
+        TxVote::new(&alice_pubkey, 0, &underland_pubkey, 1, &alice_key),
+    ]);
+    assert_eq!(
+        block.transactions[6].status().unwrap_err().description(),
+        Some("Fan has already cast all the votes")
+    );
+    let snapshot = testkit.snapshot();
+    let schema = VotesSchema::new(&snapshot);
+    assert_eq!(schema.ballot(0, &alice_pubkey).unwrap().votes(), 2);
+    assert_eq!(schema.tally(0, &wonderland_pubkey).unwrap().votes(), 1);
+    assert_eq!(schema.tally(0, &underland_pubkey).unwrap().votes(), 1);
+}
+
+
+#[test]
+fn test_create_election_without_votes() {
+    let mut testkit = init_testkit();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    let (author_pubkey, author_key) = crypto::gen_keypair();
+    let block = testkit.create_block_with_transactions(txvec![
+        register_team(&wonderland_pubkey, "Wonderland"),
+        TxCreateElection::new(&author_pubkey, "Best goal", vec![wonderland_pubkey],
+                              0, u64::max_value(), 0, BallotMode::Single as u8, 0, 0, &author_key),
+    ]);
+    assert_eq!(
+        block.transactions[1].status().unwrap_err().description(),
+        Some("Fans must have at least one vote")
+    );
+}
+
+
+#[test]
+fn test_change_vote() {
+    let mut testkit = init_testkit();
+    let (alice_pubkey, alice_key) = crypto::gen_keypair();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    let (underland_pubkey, _) = crypto::gen_keypair();
+    let vote = TxVote::new(&alice_pubkey, 0, &wonderland_pubkey, 0, &alice_key);
+    let change = TxChangeVote::new(&alice_pubkey, 0, &wonderland_pubkey, &underland_pubkey, 0, &alice_key);
+    testkit.create_block_with_transactions(txvec![
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        register_team(&wonderland_pubkey, "Wonderland"),
+        register_team(&underland_pubkey, "Underland"),
+        create_election(vec![wonderland_pubkey, underland_pubkey]),
+        vote.clone(),
+    ]);
+    let block = testkit.create_block_with_transactions(txvec![change.clone()]);
+    assert!(block.transactions[0].status().is_ok());
+    let snapshot = testkit.snapshot();
+    let schema = VotesSchema::new(&snapshot);
+    assert_eq!(schema.tally(0, &wonderland_pubkey).unwrap().votes(), 0);
+    assert_eq!(schema.tally(0, &underland_pubkey).unwrap().votes(), 1);
+    let ballot = schema.ballot(0, &alice_pubkey).unwrap();
+    assert_eq!(ballot.votes(), 1);
+    assert_eq!(ballot.votes_for(&wonderland_pubkey), 0);
+    assert_eq!(ballot.votes_for(&underland_pubkey), 1);
+    assert_eq!(*ballot.vote_hash(), change.hash());
+    let history = schema.ballot_history(0, &alice_pubkey);
+    assert_eq!(history.iter().collect::<Vec<_>>(), vec![vote.hash(), change.hash()]);
+    assert_eq!(ballot.history_len(), 2);
+    assert_eq!(*ballot.history_hash(), history.merkle_root());
+
+    let voters = schema.voters(0, &wonderland_pubkey);
+    assert_eq!(voters.len(), 2);
+    assert_eq!(voters.get(1).unwrap().votes(), -1);
+    assert_eq!(*voters.get(1).unwrap().tx_hash(), change.hash());
+    let tally = schema.tally(0, &wonderland_pubkey).unwrap();
+    assert_eq!(tally.voters_len(), 2);
+    assert_eq!(*tally.voters_hash(), voters.merkle_root());
+
+    let leaderboard: Vec<_> = schema.leaderboard(0).iter().map(|key| parse_leaderboard_key(&key)).collect();
+    assert_eq!(leaderboard, vec![(1, underland_pubkey), (0, wonderland_pubkey)]);
+    assert_eq!(schema.team_rank(0, &underland_pubkey), Some(0));
+    assert_eq!(schema.team_rank(0, &wonderland_pubkey), Some(1));
+}
+
+
+#[test]
+fn test_rank_with_ties() {
+    let mut testkit = init_testkit();
+    let (alice_pubkey, alice_key) = crypto::gen_keypair();
+    let (bob_pubkey, bob_key) = crypto::gen_keypair();
+    let mut teams: Vec<_> = (0..4).map(|_| crypto::gen_keypair().0).collect();
+    teams.sort();
+    let (first, second) = (teams[0], teams[1]);
+    testkit.create_block_with_transactions(txvec![
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        TxCreateWallet::new(&bob_pubkey, "Bob", &bob_key),
+        register_team(&teams[0], "Wonderland"),
+        register_team(&teams[1], "Underland"),
+        register_team(&teams[2], "Overland"),
+        register_team(&teams[3], "Neverland"),
+        create_election(teams.clone()),
+        TxVote::new(&alice_pubkey, 0, &second, 0, &alice_key),
+        TxVote::new(&bob_pubkey, 0, &first, 0, &bob_key),
+    ]);
+    {
         let snapshot = testkit.snapshot();
         let schema = VotesSchema::new(&snapshot);
-        (schema.fan_wallet(&alice_pubkey), schema.team_wallet(&wonderland_pubkey))
-    };
-    if let (Some(alice_wallet), Some(wonderland_wallet)) = wallets {
-        assert_eq!(alice_wallet.voted(), true);
-        assert_eq!(wonderland_wallet.votes(), 1);
-    } else {
-        panic!("Wallets not persisted");
+        let counts: Vec<_> = schema.vote_counts(0).iter()
+            .map(|(key, count)| (u64::max_value() - key, count))
+            .collect();
+        assert_eq!(counts, vec![(1, 2), (0, 2)]);
+        let ranks: Vec<_> = teams.iter().map(|team| schema.team_rank(0, team).unwrap()).collect();
+        assert_eq!(ranks, vec![0, 1, 2, 3]);
+        let page = |from, limit| -> Vec<PublicKey> {
+            schema.rating(0, 0, u64::max_value(), from, limit).unwrap()
+                .iter()
+                .map(|tally| *tally.team())
+                .collect()
+        };
+        assert_eq!(page(1, 2), vec![teams[1], teams[2]]);
+        assert_eq!(page(2, 10), vec![teams[2], teams[3]]);
+        assert_eq!(page(3, 10), vec![teams[3]]);
+        assert!(page(4, 10).is_empty());
+        let rating = schema.rating(0, 1, u64::max_value(), 1, 10).unwrap();
+        assert_eq!(rating.iter().map(|tally| *tally.team()).collect::<Vec<_>>(), vec![second]);
+        assert!(schema.rating(0, 1, u64::max_value(), 2, 10).unwrap().is_empty());
     }
+
+    testkit.create_block_with_transactions(txvec![
+        TxChangeVote::new(&alice_pubkey, 0, &second, &first, 0, &alice_key),
+    ]);
+    let snapshot = testkit.snapshot();
+    let schema = VotesSchema::new(&snapshot);
+    let counts: Vec<_> = schema.vote_counts(0).iter()
+        .map(|(key, count)| (u64::max_value() - key, count))
+        .collect();
+    assert_eq!(counts, vec![(2, 1), (0, 3)]);
+    let ranks: Vec<_> = teams.iter().map(|team| schema.team_rank(0, team).unwrap()).collect();
+    assert_eq!(ranks, vec![0, 1, 2, 3]);
+    let rating = schema.rating(0, 0, 0, 2, 10).unwrap();
+    assert_eq!(rating.iter().map(|tally| *tally.team()).collect::<Vec<_>>(), vec![teams[3]]);
+}
+
+#[test]
+fn test_rating_at_height() {
+    let mut testkit = init_testkit();
+    let (alice_pubkey, alice_key) = crypto::gen_keypair();
+    let (bob_pubkey, bob_key) = crypto::gen_keypair();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    let (underland_pubkey, _) = crypto::gen_keypair();
+    testkit.create_block_with_transactions(txvec![
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        TxCreateWallet::new(&bob_pubkey, "Bob", &bob_key),
+        register_team(&wonderland_pubkey, "Wonderland"),
+        register_team(&underland_pubkey, "Underland"),
+        create_election(vec![wonderland_pubkey, underland_pubkey]),
+        TxVote::new(&alice_pubkey, 0, &wonderland_pubkey, 0, &alice_key),
+    ]);
+    testkit.create_block_with_transactions(txvec![
+        TxVote::new(&bob_pubkey, 0, &underland_pubkey, 0, &bob_key),
+    ]);
+    testkit.create_block_with_transactions(txvec![
+        TxChangeVote::new(&alice_pubkey, 0, &wonderland_pubkey, &underland_pubkey, 0, &alice_key),
+    ]);
+    testkit.create_block();
+
+    let snapshot = testkit.snapshot();
+    let schema = VotesSchema::new(&snapshot);
+    assert_eq!(schema.election(0).unwrap().created_height(), 1);
+    let rating = |height: u64| -> Vec<(PublicKey, u64)> {
+        schema.rating_at(0, height)
+            .unwrap()
+            .iter()
+            .map(|tally| (*tally.team(), tally.votes()))
+            .collect()
+    };
+    // The election is created in block 1
+    assert!(schema.rating_at(0, 0).is_none());
+    assert_eq!(rating(1), vec![(wonderland_pubkey, 1), (underland_pubkey, 0)]);
+    assert_eq!(rating(2).iter().map(|&(_, votes)| votes).collect::<Vec<_>>(), vec![1, 1]);
+    assert_eq!(rating(3), vec![(underland_pubkey, 2), (wonderland_pubkey, 0)]);
+    assert_eq!(rating(4), rating(3));
+    assert!(schema.rating_at(1, 1).is_none());
+
+    // Alice's change in block 3 moves her vote from Wonderland to Underland
+    assert_eq!(schema.team_history(0, &underland_pubkey), vec![(2, 1), (3, 2)]);
+    assert_eq!(schema.team_history(0, &wonderland_pubkey), vec![(1, 1), (3, 0)]);
+    let delta = schema.tally_deltas(0, &wonderland_pubkey).get(1).unwrap();
+    assert_eq!((delta.votes(), delta.total()), (-1, 0));
+    assert_eq!(schema.tally_at(0, &underland_pubkey, 2), 1);
+    let roots = schema.history_roots();
+    assert_eq!(schema.state_hash()[HISTORY_ROOTS_TABLE], roots.merkle_root());
+    assert_eq!(
+        roots.get(&election_key(0, &wonderland_pubkey)),
+        Some(schema.tally_deltas(0, &wonderland_pubkey).merkle_root())
+    );
 }
 
 
 #[test]
-fn test_multiple_vote() {
+fn test_change_vote_without_vote() {
     let mut testkit = init_testkit();
     let (alice_pubkey, alice_key) = crypto::gen_keypair();
-    let (wonderland_pubkey, wonderland_key) = crypto::gen_keypair();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    let (underland_pubkey, _) = crypto::gen_keypair();
+    let block = testkit.create_block_with_transactions(txvec![
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        register_team(&wonderland_pubkey, "Wonderland"),
+        register_team(&underland_pubkey, "Underland"),
+        create_election(vec![wonderland_pubkey, underland_pubkey]),
+        TxVote::new(&alice_pubkey, 0, &wonderland_pubkey, 0, &alice_key),
+        TxChangeVote::new(&alice_pubkey, 0, &underland_pubkey, &wonderland_pubkey, 0, &alice_key),
+    ]);
+    assert_eq!(
+        block.transactions[5].status().unwrap_err().description(),
+        Some("Fan hasn't voted for the team")
+    );
+    let snapshot = testkit.snapshot();
+    let schema = VotesSchema::new(&snapshot);
+    assert_eq!(schema.tally(0, &wonderland_pubkey).unwrap().votes(), 1);
+    assert_eq!(schema.ballot_history(0, &alice_pubkey).len(), 1);
+}
+
+
+#[test]
+fn test_change_vote_to_same_team() {
+    let mut testkit = init_testkit();
+    let (alice_pubkey, alice_key) = crypto::gen_keypair();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    let block = testkit.create_block_with_transactions(txvec![
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        register_team(&wonderland_pubkey, "Wonderland"),
+        create_election(vec![wonderland_pubkey]),
+        TxVote::new(&alice_pubkey, 0, &wonderland_pubkey, 0, &alice_key),
+        TxChangeVote::new(&alice_pubkey, 0, &wonderland_pubkey, &wonderland_pubkey, 0, &alice_key),
+    ]);
+    let error = block.transactions[4].status().unwrap_err();
+    assert_eq!(error.error_type(), TransactionErrorType::Code(Error::SameTeam as u8));
+    assert_eq!(error.description(), Some("New team is the same as the old one"));
+    let snapshot = testkit.snapshot();
+    let schema = VotesSchema::new(&snapshot);
+    assert_eq!(schema.ballot_history(0, &alice_pubkey).len(), 1);
+    assert_eq!(schema.voters(0, &wonderland_pubkey).len(), 1);
+    assert_eq!(schema.votes_log().len(), 1);
+}
+
+
+#[test]
+fn test_change_vote_after_window() {
+    let mut testkit = init_testkit();
+    let (alice_pubkey, alice_key) = crypto::gen_keypair();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    let (underland_pubkey, _) = crypto::gen_keypair();
     testkit.create_block_with_transactions(txvec![
-        TxCreateWallet::new(&alice_pubkey, "Alice", false, &alice_key),
-        TxCreateWallet::new(&wonderland_pubkey, "Wonderland", true, &wonderland_key),
-        TxVote::new(&alice_pubkey, &wonderland_pubkey, 0, &alice_key),
-        TxVote::new(&alice_pubkey, &wonderland_pubkey, 1, &alice_key),
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        register_team(&wonderland_pubkey, "Wonderland"),
+        register_team(&underland_pubkey, "Underland"),
+        create_election_with_window(vec![wonderland_pubkey, underland_pubkey], 1, 2),
+        TxVote::new(&alice_pubkey, 0, &wonderland_pubkey, 0, &alice_key),
     ]);
-    let wallet = {
+    let block = testkit.create_block_with_transactions(txvec![
+        TxChangeVote::new(&alice_pubkey, 0, &wonderland_pubkey, &underland_pubkey, 0, &alice_key),
+    ]);
+    assert_eq!(
+        block.transactions[0].status().unwrap_err().description(),
+        Some("Voting is already closed")
+    );
+}
+
+
+#[test]
+fn test_revoke_vote() {
+    let mut testkit = init_testkit();
+    let (alice_pubkey, alice_key) = crypto::gen_keypair();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    let (underland_pubkey, _) = crypto::gen_keypair();
+    let revoke = TxRevokeVote::new(&alice_pubkey, 0, 0, &alice_key);
+    testkit.create_block_with_transactions(txvec![
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        register_team(&wonderland_pubkey, "Wonderland"),
+        register_team(&underland_pubkey, "Underland"),
+        create_election(vec![wonderland_pubkey, underland_pubkey]),
+        TxVote::new(&alice_pubkey, 0, &wonderland_pubkey, 0, &alice_key),
+    ]);
+    let block = testkit.create_block_with_transactions(txvec![revoke.clone()]);
+    assert!(block.transactions[0].status().is_ok());
+    {
         let snapshot = testkit.snapshot();
         let schema = VotesSchema::new(&snapshot);
-        schema.team_wallet(&wonderland_pubkey)
-    };
-    if let Some(wonderland_wallet) = wallet {
-        assert_eq!(wonderland_wallet.votes(), 1);
-    } else {
-        panic!("Wallets not persisted");
+        assert_eq!(schema.tally(0, &wonderland_pubkey).unwrap().votes(), 0);
+        let ballot = schema.ballot(0, &alice_pubkey).unwrap();
+        assert!(!ballot.voted());
+        assert!(ballot.revoked());
+        assert_eq!(*ballot.vote_hash(), revoke.hash());
+        assert_eq!(schema.ballot_history(0, &alice_pubkey).len(), 2);
     }
+
+    // Revoked vote can be cast again.
+    let block = testkit.create_block_with_transactions(txvec![
+        TxVote::new(&alice_pubkey, 0, &underland_pubkey, 0, &alice_key),
+    ]);
+    assert!(block.transactions[0].status().is_ok());
+    let snapshot = testkit.snapshot();
+    let schema = VotesSchema::new(&snapshot);
+    assert_eq!(schema.tally(0, &underland_pubkey).unwrap().votes(), 1);
+    assert!(!schema.ballot(0, &alice_pubkey).unwrap().revoked());
 }
 
 
 #[test]
-fn test_vote_with_for_existing_team() {
+fn test_revoke_vote_twice() {
     let mut testkit = init_testkit();
     let (alice_pubkey, alice_key) = crypto::gen_keypair();
-    let (wonderland_pubkey, wonderland_key) = crypto::gen_keypair();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    let block = testkit.create_block_with_transactions(txvec![
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        register_team(&wonderland_pubkey, "Wonderland"),
+        create_election(vec![wonderland_pubkey]),
+        TxVote::new(&alice_pubkey, 0, &wonderland_pubkey, 0, &alice_key),
+        TxRevokeVote::new(&alice_pubkey, 0, 0, &alice_key),
+        TxRevokeVote::new(&alice_pubkey, 0, 1, &alice_key),
+    ]);
+    assert_eq!(
+        block.transactions[5].status().unwrap_err().description(),
+        Some("Vote is already revoked")
+    );
+    let snapshot = testkit.snapshot();
+    let schema = VotesSchema::new(&snapshot);
+    assert_eq!(schema.tally(0, &wonderland_pubkey).unwrap().votes(), 0);
+    assert_eq!(schema.ballot_history(0, &alice_pubkey).len(), 2);
+}
+
+
+#[test]
+fn test_revoke_vote_never_cast() {
+    let mut testkit = init_testkit();
+    let (alice_pubkey, alice_key) = crypto::gen_keypair();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    let block = testkit.create_block_with_transactions(txvec![
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        register_team(&wonderland_pubkey, "Wonderland"),
+        create_election(vec![wonderland_pubkey]),
+        TxRevokeVote::new(&alice_pubkey, 0, 0, &alice_key),
+    ]);
+    assert_eq!(
+        block.transactions[3].status().unwrap_err().description(),
+        Some("Fan hasn't voted in the election")
+    );
+    let snapshot = testkit.snapshot();
+    let schema = VotesSchema::new(&snapshot);
+    assert!(schema.ballot(0, &alice_pubkey).is_none());
+}
+
+
+#[test]
+fn test_votes_log() {
+    let mut testkit = init_testkit();
+    let (alice_pubkey, alice_key) = crypto::gen_keypair();
+    let (bob_pubkey, bob_key) = crypto::gen_keypair();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    let alice_vote = TxVote::new(&alice_pubkey, 0, &wonderland_pubkey, 0, &alice_key);
+    let bob_vote = TxVote::new(&bob_pubkey, 0, &wonderland_pubkey, 0, &bob_key);
+    let alice_revoke = TxRevokeVote::new(&alice_pubkey, 0, 0, &alice_key);
     testkit.create_block_with_transactions(txvec![
-        TxCreateWallet::new(&alice_pubkey, "Alice", false, &alice_key),
-        TxVote::new(&alice_pubkey, &wonderland_pubkey, 0, &alice_key),
-        TxCreateWallet::new(&wonderland_pubkey, "Wonderland", true, &wonderland_key),
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        TxCreateWallet::new(&bob_pubkey, "Bob", &bob_key),
+        register_team(&wonderland_pubkey, "Wonderland"),
+        create_election(vec![wonderland_pubkey]),
+        alice_vote.clone(),
+        // Rejected votes are not logged
+        TxVote::new(&alice_pubkey, 0, &wonderland_pubkey, 1, &alice_key),
+    ]);
+    testkit.create_block_with_transactions(txvec![bob_vote.clone()]);
+    testkit.create_block_with_transactions(txvec![alice_revoke.clone()]);
+    let snapshot = testkit.snapshot();
+    let schema = VotesSchema::new(&snapshot);
+    assert_eq!(
+        schema.votes_log().iter().collect::<Vec<_>>(),
+        vec![alice_vote.hash(), bob_vote.hash(), alice_revoke.hash()]
+    );
+    assert_eq!(schema.state_hash()[VOTES_LOG_TABLE], schema.votes_log().merkle_root());
+}
+
+
+fn create_points_election(candidates: Vec<PublicKey>, max_votes: u64) -> TxCreateElection {
+    let (pubkey, key) = crypto::gen_keypair();
+    TxCreateElection::new(&pubkey, "Best goal", candidates, 0, u64::max_value(), max_votes,
+                          BallotMode::Points as u8, 0, 0, &key)
+}
+
+
+#[test]
+fn test_multi_vote() {
+    let mut testkit = init_testkit();
+    let (alice_pubkey, alice_key) = crypto::gen_keypair();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    let (underland_pubkey, _) = crypto::gen_keypair();
+    let block = testkit.create_block_with_transactions(txvec![
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        register_team(&wonderland_pubkey, "Wonderland"),
+        register_team(&underland_pubkey, "Underland"),
+        create_points_election(vec![wonderland_pubkey, underland_pubkey], 5),
+        TxMultiVote::new(&alice_pubkey, 0, vec![
+            TeamVotes::new(&wonderland_pubkey, 3),
+            TeamVotes::new(&underland_pubkey, 1),
+        ], 0, &alice_key),
+        // Only one point is left
+        TxMultiVote::new(&alice_pubkey, 0, vec![TeamVotes::new(&underland_pubkey, 2)], 1, &alice_key),
+        TxMultiVote::new(&alice_pubkey, 0, vec![TeamVotes::new(&underland_pubkey, 1)], 2, &alice_key),
+    ]);
+    assert!(block.transactions[4].status().is_ok());
+    assert_eq!(
+        block.transactions[5].status().unwrap_err().description(),
+        Some("Fan doesn't have enough votes left")
+    );
+    assert!(block.transactions[6].status().is_ok());
+    let snapshot = testkit.snapshot();
+    let schema = VotesSchema::new(&snapshot);
+    let ballot = schema.ballot(0, &alice_pubkey).unwrap();
+    assert_eq!(ballot.votes(), 5);
+    assert_eq!(ballot.votes_for(&wonderland_pubkey), 3);
+    assert_eq!(ballot.votes_for(&underland_pubkey), 2);
+    assert_eq!(ballot.history_len(), 2);
+    assert_eq!(schema.tally(0, &wonderland_pubkey).unwrap().votes(), 3);
+    assert_eq!(schema.tally(0, &underland_pubkey).unwrap().votes(), 2);
+}
+
+
+#[test]
+fn test_multi_vote_with_invalid_allocations() {
+    let mut testkit = init_testkit();
+    let (alice_pubkey, alice_key) = crypto::gen_keypair();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    let (underland_pubkey, _) = crypto::gen_keypair();
+    let block = testkit.create_block_with_transactions(txvec![
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        register_team(&wonderland_pubkey, "Wonderland"),
+        register_team(&underland_pubkey, "Underland"),
+        create_points_election(vec![wonderland_pubkey, underland_pubkey], 5),
+        TxMultiVote::new(&alice_pubkey, 0, vec![
+            TeamVotes::new(&wonderland_pubkey, 1),
+            TeamVotes::new(&wonderland_pubkey, 1),
+        ], 0, &alice_key),
+        TxMultiVote::new(&alice_pubkey, 0, vec![TeamVotes::new(&wonderland_pubkey, 0)], 1, &alice_key),
+        TxMultiVote::new(&alice_pubkey, 0, vec![], 2, &alice_key),
+    ]);
+    for tx in &block.transactions[4..7] {
+        assert_eq!(
+            tx.status().unwrap_err().description(),
+            Some("Allocations must give votes to distinct teams")
+        );
+    }
+    let snapshot = testkit.snapshot();
+    let schema = VotesSchema::new(&snapshot);
+    assert!(schema.ballot(0, &alice_pubkey).is_none());
+}
+
+
+#[test]
+fn test_multi_vote_overflow() {
+    let mut testkit = init_testkit();
+    let (alice_pubkey, alice_key) = crypto::gen_keypair();
+    let (bob_pubkey, bob_key) = crypto::gen_keypair();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    let block = testkit.create_block_with_transactions(txvec![
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        TxCreateWallet::new(&bob_pubkey, "Bob", &bob_key),
+        register_team(&wonderland_pubkey, "Wonderland"),
+        create_points_election(vec![wonderland_pubkey], u64::max_value()),
+        TxMultiVote::new(&alice_pubkey, 0, vec![
+            TeamVotes::new(&wonderland_pubkey, i64::max_value() as u64),
+        ], 0, &alice_key),
+        TxMultiVote::new(&bob_pubkey, 0, vec![TeamVotes::new(&wonderland_pubkey, 1)], 0, &bob_key),
+    ]);
+    assert!(block.transactions[4].status().is_ok());
+    let error = block.transactions[5].status().unwrap_err();
+    assert_eq!(error.error_type(), TransactionErrorType::Code(Error::VoteCountOverflow as u8));
+    assert_eq!(error.description(), Some("Vote count overflows the tally"));
+    let snapshot = testkit.snapshot();
+    let schema = VotesSchema::new(&snapshot);
+    assert!(schema.ballot(0, &bob_pubkey).is_none());
+    assert_eq!(schema.tally(0, &wonderland_pubkey).unwrap().votes(), i64::max_value() as u64);
+}
+
+
+#[test]
+fn test_ballot_mode_mismatch() {
+    let mut testkit = init_testkit();
+    let (alice_pubkey, alice_key) = crypto::gen_keypair();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    let (author_pubkey, author_key) = crypto::gen_keypair();
+    let block = testkit.create_block_with_transactions(txvec![
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        register_team(&wonderland_pubkey, "Wonderland"),
+        create_election(vec![wonderland_pubkey]),
+        create_points_election(vec![wonderland_pubkey], 5),
+        TxMultiVote::new(&alice_pubkey, 0, vec![TeamVotes::new(&wonderland_pubkey, 1)], 0, &alice_key),
+        TxVote::new(&alice_pubkey, 1, &wonderland_pubkey, 0, &alice_key),
+        TxCreateElection::new(&author_pubkey, "Best goal", vec![wonderland_pubkey],
+                              0, u64::max_value(), 1, 42, 0, 0, &author_key),
     ]);
-    let wallets = {
+    for tx in &block.transactions[4..6] {
+        assert_eq!(
+            tx.status().unwrap_err().description(),
+            Some("Election doesn't accept this kind of ballot")
+        );
+    }
+    assert_eq!(
+        block.transactions[6].status().unwrap_err().description(),
+        Some("Unknown ballot mode")
+    );
+}
+
+
+fn create_ranked_election(candidates: Vec<PublicKey>) -> TxCreateElection {
+    let (pubkey, key) = crypto::gen_keypair();
+    TxCreateElection::new(&pubkey, "Top 3", candidates, 0, u64::max_value(), 1,
+                          BallotMode::Ranked as u8, 0, 0, &key)
+}
+
+
+#[test]
+fn test_ranked_vote() {
+    let mut testkit = init_testkit();
+    let (alice_pubkey, alice_key) = crypto::gen_keypair();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    let (underland_pubkey, _) = crypto::gen_keypair();
+    let block = testkit.create_block_with_transactions(txvec![
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        register_team(&wonderland_pubkey, "Wonderland"),
+        register_team(&underland_pubkey, "Underland"),
+        create_ranked_election(vec![wonderland_pubkey, underland_pubkey]),
+        TxRankedVote::new(&alice_pubkey, 0, vec![wonderland_pubkey, underland_pubkey], 0, &alice_key),
+    ]);
+    assert!(block.transactions[4].status().is_ok());
+    {
         let snapshot = testkit.snapshot();
         let schema = VotesSchema::new(&snapshot);
-        (schema.fan_wallet(&alice_pubkey), schema.team_wallet(&wonderland_pubkey))
-    };
-    if let (Some(alice_wallet), Some(wonderland_wallet)) = wallets {
-        assert_eq!(alice_wallet.voted(), false);
-        assert_eq!(wonderland_wallet.votes(), 0);
-    } else {
-        panic!("Transfer occurred");
+        let ranking = schema.ranking(0, &alice_pubkey).unwrap();
+        assert_eq!(ranking.teams(), vec![wonderland_pubkey, underland_pubkey]);
+        assert_eq!(schema.tally(0, &wonderland_pubkey).unwrap().votes(), 1);
     }
+
+    // The new ranking replaces the previous one
+    testkit.create_block_with_transactions(txvec![
+        TxRankedVote::new(&alice_pubkey, 0, vec![underland_pubkey], 1, &alice_key),
+    ]);
+    let snapshot = testkit.snapshot();
+    let schema = VotesSchema::new(&snapshot);
+    assert_eq!(schema.election_rankings(0), vec![(vec![underland_pubkey], 1)]);
+    assert_eq!(schema.tally(0, &wonderland_pubkey).unwrap().votes(), 0);
+    assert_eq!(schema.tally(0, &underland_pubkey).unwrap().votes(), 1);
+    assert_eq!(schema.votes_log().len(), 2);
 }
 
 
 #[test]
-fn test_vote_as_non_existing_fan() {
+fn test_ranked_vote_with_invalid_ranking() {
     let mut testkit = init_testkit();
     let (alice_pubkey, alice_key) = crypto::gen_keypair();
-    let (wonderland_pubkey, wonderland_key) = crypto::gen_keypair();
-    testkit.create_block_with_transactions(txvec![
-        TxCreateWallet::new(&wonderland_pubkey, "Wonderland", true, &wonderland_key),
-        TxVote::new(&alice_pubkey, &wonderland_pubkey, 0, &alice_key),
-        TxCreateWallet::new(&alice_pubkey, "Alice", false, &alice_key),
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    let (underland_pubkey, _) = crypto::gen_keypair();
+    let block = testkit.create_block_with_transactions(txvec![
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        register_team(&wonderland_pubkey, "Wonderland"),
+        register_team(&underland_pubkey, "Underland"),
+        create_ranked_election(vec![wonderland_pubkey]),
+        TxRankedVote::new(&alice_pubkey, 0, vec![wonderland_pubkey, wonderland_pubkey], 0, &alice_key),
+        TxRankedVote::new(&alice_pubkey, 0, vec![], 1, &alice_key),
+        TxRankedVote::new(&alice_pubkey, 0, vec![wonderland_pubkey, underland_pubkey], 2, &alice_key),
     ]);
-    let wallets = {
+    for tx in &block.transactions[4..6] {
+        assert_eq!(
+            tx.status().unwrap_err().description(),
+            Some("Ranking must list distinct candidates")
+        );
+    }
+    assert_eq!(
+        block.transactions[6].status().unwrap_err().description(),
+        Some("Team is not a candidate in the election")
+    );
+    let snapshot = testkit.snapshot();
+    let schema = VotesSchema::new(&snapshot);
+    assert!(schema.ranking(0, &alice_pubkey).is_none());
+}
+
+
+#[test]
+fn test_change_and_revoke_ranked_vote() {
+    let mut testkit = init_testkit();
+    let (alice_pubkey, alice_key) = crypto::gen_keypair();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    let (underland_pubkey, _) = crypto::gen_keypair();
+    let block = testkit.create_block_with_transactions(txvec![
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        register_team(&wonderland_pubkey, "Wonderland"),
+        register_team(&underland_pubkey, "Underland"),
+        create_ranked_election(vec![wonderland_pubkey, underland_pubkey]),
+        TxRankedVote::new(&alice_pubkey, 0, vec![wonderland_pubkey, underland_pubkey], 0, &alice_key),
+        TxChangeVote::new(&alice_pubkey, 0, &wonderland_pubkey, &underland_pubkey, 0, &alice_key),
+        TxRevokeVote::new(&alice_pubkey, 0, 0, &alice_key),
+    ]);
+    for tx in &block.transactions[5..7] {
+        assert_eq!(
+            tx.status().unwrap_err().description(),
+            Some("Election doesn't accept this kind of ballot")
+        );
+    }
+    let snapshot = testkit.snapshot();
+    let schema = VotesSchema::new(&snapshot);
+    assert_eq!(schema.ranking(0, &alice_pubkey).unwrap().teams(),
+               vec![wonderland_pubkey, underland_pubkey]);
+    assert_eq!(schema.tally(0, &wonderland_pubkey).unwrap().votes(), 1);
+}
+
+#[test]
+fn test_commit_reveal_vote() {
+    let mut testkit = init_testkit();
+    let (alice_pubkey, alice_key) = crypto::gen_keypair();
+    let (bob_pubkey, bob_key) = crypto::gen_keypair();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    let (author_pubkey, author_key) = crypto::gen_keypair();
+    let alice_salt = crypto::hash(b"alice salt");
+    let alice_commitment = vote_commitment(&wonderland_pubkey, &alice_salt);
+    let bob_commitment = vote_commitment(&wonderland_pubkey, &crypto::hash(b"bob salt"));
+
+    // Commit phase is [0, 3), reveal phase is [3, 10)
+    let block = testkit.create_block_with_transactions(txvec![
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        TxCreateWallet::new(&bob_pubkey, "Bob", &bob_key),
+        register_team(&wonderland_pubkey, "Wonderland"),
+        TxCreateElection::new(&author_pubkey, "World Cup winner", vec![wonderland_pubkey],
+                              0, 10, 1, BallotMode::Single as u8, 3, 0, &author_key),
+        TxCommitVote::new(&alice_pubkey, 0, &alice_commitment, 0, &alice_key),
+        TxCommitVote::new(&bob_pubkey, 0, &bob_commitment, 0, &bob_key),
+        TxRevealVote::new(&alice_pubkey, 0, &wonderland_pubkey, &alice_salt, 0, &alice_key),
+        TxVote::new(&alice_pubkey, 0, &wonderland_pubkey, 0, &alice_key),
+    ]);
+    assert!(block.transactions[4].status().is_ok());
+    assert_eq!(
+        block.transactions[6].status().unwrap_err().description(),
+        Some("Reveal phase has not started yet")
+    );
+    assert_eq!(
+        block.transactions[7].status().unwrap_err().description(),
+        Some("Election doesn't accept this kind of ballot")
+    );
+    assert_eq!(VotesSchema::new(&testkit.snapshot()).tally(0, &wonderland_pubkey).unwrap().votes(), 0);
+
+    testkit.create_block();
+    let block = testkit.create_block_with_transactions(txvec![
+        TxCommitVote::new(&alice_pubkey, 0, &alice_commitment, 1, &alice_key),
+        TxRevealVote::new(&alice_pubkey, 0, &wonderland_pubkey, &crypto::hash(b"wrong"), 0, &alice_key),
+        TxRevealVote::new(&alice_pubkey, 0, &wonderland_pubkey, &alice_salt, 1, &alice_key),
+        TxRevealVote::new(&alice_pubkey, 0, &wonderland_pubkey, &alice_salt, 2, &alice_key),
+    ]);
+    assert_eq!(
+        block.transactions[0].status().unwrap_err().description(),
+        Some("Commit phase is over")
+    );
+    assert_eq!(
+        block.transactions[1].status().unwrap_err().description(),
+        Some("Team and salt don't match the commitment")
+    );
+    assert!(block.transactions[2].status().is_ok());
+    assert_eq!(
+        block.transactions[3].status().unwrap_err().description(),
+        Some("Vote is already revealed")
+    );
+
+    let snapshot = testkit.snapshot();
+    let schema = VotesSchema::new(&snapshot);
+    assert_eq!(schema.tally(0, &wonderland_pubkey).unwrap().votes(), 1);
+    assert!(schema.commitment(0, &alice_pubkey).unwrap().revealed());
+    assert_eq!(schema.ballot(0, &alice_pubkey).unwrap().votes_for(&wonderland_pubkey), 1);
+    // Bob's vote is not counted
+    assert_eq!(schema.unrevealed_fans(0), vec![bob_pubkey]);
+}
+
+
+#[test]
+fn test_create_secret_election_with_invalid_reveal_height() {
+    let mut testkit = init_testkit();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    let (author_pubkey, author_key) = crypto::gen_keypair();
+    let block = testkit.create_block_with_transactions(txvec![
+        register_team(&wonderland_pubkey, "Wonderland"),
+        TxCreateElection::new(&author_pubkey, "Best goal", vec![wonderland_pubkey],
+                              5, 10, 1, BallotMode::Single as u8, 5, 0, &author_key),
+        TxCreateElection::new(&author_pubkey, "Best goal", vec![wonderland_pubkey],
+                              5, 10, 1, BallotMode::Single as u8, 10, 0, &author_key),
+        TxCreateElection::new(&author_pubkey, "Best goal", vec![wonderland_pubkey],
+                              5, 10, 1, BallotMode::Points as u8, 7, 0, &author_key),
+    ]);
+    for tx in &block.transactions[1..3] {
+        assert_eq!(tx.status().unwrap_err().description(), Some("Voting window is empty"));
+    }
+    assert_eq!(
+        block.transactions[3].status().unwrap_err().description(),
+        Some("Election doesn't accept this kind of ballot")
+    );
+}
+
+
+#[test]
+fn test_delegate() {
+    let mut testkit = init_testkit();
+    let (alice_pubkey, alice_key) = crypto::gen_keypair();
+    let (bob_pubkey, bob_key) = crypto::gen_keypair();
+    let (carol_pubkey, carol_key) = crypto::gen_keypair();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    let block = testkit.create_block_with_transactions(txvec![
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        TxCreateWallet::new(&bob_pubkey, "Bob", &bob_key),
+        TxCreateWallet::new(&carol_pubkey, "Carol", &carol_key),
+        register_team(&wonderland_pubkey, "Wonderland"),
+        create_election(vec![wonderland_pubkey]),
+        // Alice -> Bob -> Carol
+        TxDelegate::new(&alice_pubkey, &bob_pubkey, 0, &alice_key),
+        TxDelegate::new(&bob_pubkey, &carol_pubkey, 0, &bob_key),
+        TxVote::new(&carol_pubkey, 0, &wonderland_pubkey, 0, &carol_key),
+    ]);
+    assert!(block.transactions[7].status().is_ok());
+    {
         let snapshot = testkit.snapshot();
         let schema = VotesSchema::new(&snapshot);
-        (schema.fan_wallet(&alice_pubkey), schema.team_wallet(&wonderland_pubkey))
-    };
-    if let (Some(alice_wallet), Some(wonderland_wallet)) = wallets {
-        assert_eq!(alice_wallet.voted(), false);
-        assert_eq!(wonderland_wallet.votes(), 0);
-    } else {
-        panic!("Transfer occurred");
+        assert_eq!(schema.fan_wallet(&alice_pubkey).unwrap().delegate(), &bob_pubkey);
+        assert_eq!(schema.fan_wallet(&bob_pubkey).unwrap().weight(), 2);
+        assert_eq!(schema.fan_wallet(&carol_pubkey).unwrap().weight(), 3);
+        assert_eq!(schema.ballot(0, &carol_pubkey).unwrap().weight(), 3);
+        assert_eq!(schema.tally(0, &wonderland_pubkey).unwrap().votes(), 3);
+    }
+
+    // The vote of Alice is already cast by Carol
+    let block = testkit.create_block_with_transactions(txvec![
+        TxUndelegate::new(&alice_pubkey, 0, &alice_key),
+        TxVote::new(&alice_pubkey, 0, &wonderland_pubkey, 1, &alice_key),
+        TxRevokeVote::new(&carol_pubkey, 0, 0, &carol_key),
+    ]);
+    assert!(block.transactions[0].status().is_ok());
+    assert_eq!(
+        block.transactions[1].status().unwrap_err().description(),
+        Some("Vote is already cast by the delegate")
+    );
+    assert!(block.transactions[2].status().is_ok());
+    let snapshot = testkit.snapshot();
+    let schema = VotesSchema::new(&snapshot);
+    assert!(!schema.fan_wallet(&alice_pubkey).unwrap().has_delegate());
+    assert_eq!(schema.fan_wallet(&bob_pubkey).unwrap().weight(), 1);
+    assert_eq!(schema.fan_wallet(&carol_pubkey).unwrap().weight(), 2);
+    assert_eq!(schema.tally(0, &wonderland_pubkey).unwrap().votes(), 0);
+}
+
+
+#[test]
+fn test_revoke_releases_represented_fans() {
+    let mut testkit = init_testkit();
+    let (alice_pubkey, alice_key) = crypto::gen_keypair();
+    let (bob_pubkey, bob_key) = crypto::gen_keypair();
+    let (carol_pubkey, carol_key) = crypto::gen_keypair();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    testkit.create_block_with_transactions(txvec![
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        TxCreateWallet::new(&bob_pubkey, "Bob", &bob_key),
+        TxCreateWallet::new(&carol_pubkey, "Carol", &carol_key),
+        register_team(&wonderland_pubkey, "Wonderland"),
+        create_election(vec![wonderland_pubkey]),
+        TxDelegate::new(&alice_pubkey, &carol_pubkey, 0, &alice_key),
+        TxDelegate::new(&bob_pubkey, &carol_pubkey, 0, &bob_key),
+        TxVote::new(&carol_pubkey, 0, &wonderland_pubkey, 0, &carol_key),
+    ]);
+    let block = testkit.create_block_with_transactions(txvec![
+        TxUndelegate::new(&alice_pubkey, 0, &alice_key),
+        TxRevokeVote::new(&carol_pubkey, 0, 0, &carol_key),
+        TxVote::new(&carol_pubkey, 0, &wonderland_pubkey, 1, &carol_key),
+        TxVote::new(&alice_pubkey, 0, &wonderland_pubkey, 0, &alice_key),
+    ]);
+    for tx in &block.transactions[..4] {
+        assert!(tx.status().is_ok());
+    }
+    let snapshot = testkit.snapshot();
+    let schema = VotesSchema::new(&snapshot);
+    // Carol votes again for herself and Bob, Alice votes on her own
+    assert_eq!(schema.ballot(0, &carol_pubkey).unwrap().weight(), 2);
+    assert_eq!(schema.ballot(0, &alice_pubkey).unwrap().weight(), 1);
+    assert_eq!(schema.tally(0, &wonderland_pubkey).unwrap().votes(), 3);
+    let represented: Vec<PublicKey> = schema.represented_fans(0).iter().collect();
+    assert_eq!(represented, vec![bob_pubkey]);
+}
+
+
+#[test]
+fn test_delegate_in_ranked_election() {
+    let mut testkit = init_testkit();
+    let (alice_pubkey, alice_key) = crypto::gen_keypair();
+    let (bob_pubkey, bob_key) = crypto::gen_keypair();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    let (underland_pubkey, _) = crypto::gen_keypair();
+    let block = testkit.create_block_with_transactions(txvec![
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        TxCreateWallet::new(&bob_pubkey, "Bob", &bob_key),
+        register_team(&wonderland_pubkey, "Wonderland"),
+        register_team(&underland_pubkey, "Underland"),
+        create_ranked_election(vec![wonderland_pubkey, underland_pubkey]),
+        TxDelegate::new(&alice_pubkey, &bob_pubkey, 0, &alice_key),
+        TxRankedVote::new(&bob_pubkey, 0, vec![underland_pubkey], 0, &bob_key),
+        TxRankedVote::new(&alice_pubkey, 0, vec![wonderland_pubkey], 0, &alice_key),
+        TxRankedVote::new(&bob_pubkey, 0, vec![wonderland_pubkey, underland_pubkey], 1, &bob_key),
+    ]);
+    assert!(block.transactions[6].status().is_ok());
+    assert_eq!(
+        block.transactions[7].status().unwrap_err().description(),
+        Some("Vote is already cast by the delegate")
+    );
+    assert!(block.transactions[8].status().is_ok());
+    let snapshot = testkit.snapshot();
+    let schema = VotesSchema::new(&snapshot);
+    assert_eq!(schema.ranking(0, &bob_pubkey).unwrap().weight(), 2);
+    assert_eq!(schema.tally(0, &wonderland_pubkey).unwrap().votes(), 2);
+    assert_eq!(schema.tally(0, &underland_pubkey).unwrap().votes(), 0);
+    assert_eq!(schema.election_rankings(0), vec![(vec![wonderland_pubkey, underland_pubkey], 2)]);
+    assert!(schema.represented_fans(0).contains(&alice_pubkey));
+}
+
+
+#[test]
+fn test_delegate_in_secret_election() {
+    let mut testkit = init_testkit();
+    let (alice_pubkey, alice_key) = crypto::gen_keypair();
+    let (bob_pubkey, bob_key) = crypto::gen_keypair();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    let (author_pubkey, author_key) = crypto::gen_keypair();
+    let salt = crypto::hash(b"salt");
+    let commitment = vote_commitment(&wonderland_pubkey, &salt);
+    let block = testkit.create_block_with_transactions(txvec![
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        TxCreateWallet::new(&bob_pubkey, "Bob", &bob_key),
+        register_team(&wonderland_pubkey, "Wonderland"),
+        TxCreateElection::new(&author_pubkey, "World Cup winner", vec![wonderland_pubkey],
+                              0, 10, 1, BallotMode::Single as u8, 3, 0, &author_key),
+        TxDelegate::new(&alice_pubkey, &bob_pubkey, 0, &alice_key),
+        TxCommitVote::new(&bob_pubkey, 0, &commitment, 0, &bob_key),
+    ]);
+    assert!(block.transactions[5].status().is_ok());
+    testkit.create_block();
+    let block = testkit.create_block_with_transactions(txvec![
+        TxRevealVote::new(&bob_pubkey, 0, &wonderland_pubkey, &salt, 0, &bob_key),
+        TxCommitVote::new(&alice_pubkey, 0, &commitment, 0, &alice_key),
+    ]);
+    assert!(block.transactions[0].status().is_ok());
+    assert_eq!(
+        block.transactions[1].status().unwrap_err().description(),
+        Some("Vote is already cast by the delegate")
+    );
+    let snapshot = testkit.snapshot();
+    let schema = VotesSchema::new(&snapshot);
+    assert_eq!(schema.ballot(0, &bob_pubkey).unwrap().weight(), 2);
+    assert_eq!(schema.tally(0, &wonderland_pubkey).unwrap().votes(), 2);
+    assert!(schema.represented_fans(0).contains(&alice_pubkey));
+}
+
+
+#[test]
+fn test_delegate_skips_voted_fans() {
+    let mut testkit = init_testkit();
+    let (alice_pubkey, alice_key) = crypto::gen_keypair();
+    let (bob_pubkey, bob_key) = crypto::gen_keypair();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    testkit.create_block_with_transactions(txvec![
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        TxCreateWallet::new(&bob_pubkey, "Bob", &bob_key),
+        register_team(&wonderland_pubkey, "Wonderland"),
+        create_election(vec![wonderland_pubkey]),
+        TxVote::new(&alice_pubkey, 0, &wonderland_pubkey, 0, &alice_key),
+        TxDelegate::new(&alice_pubkey, &bob_pubkey, 0, &alice_key),
+        TxVote::new(&bob_pubkey, 0, &wonderland_pubkey, 0, &bob_key),
+    ]);
+    let snapshot = testkit.snapshot();
+    let schema = VotesSchema::new(&snapshot);
+    assert_eq!(schema.ballot(0, &bob_pubkey).unwrap().weight(), 1);
+    assert_eq!(schema.tally(0, &wonderland_pubkey).unwrap().votes(), 2);
+}
+
+
+#[test]
+fn test_delegate_after_delegate_voted() {
+    let mut testkit = init_testkit();
+    let (alice_pubkey, alice_key) = crypto::gen_keypair();
+    let (bob_pubkey, bob_key) = crypto::gen_keypair();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    let block = testkit.create_block_with_transactions(txvec![
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        TxCreateWallet::new(&bob_pubkey, "Bob", &bob_key),
+        register_team(&wonderland_pubkey, "Wonderland"),
+        create_election(vec![wonderland_pubkey]),
+        TxVote::new(&alice_pubkey, 0, &wonderland_pubkey, 0, &alice_key),
+        TxDelegate::new(&bob_pubkey, &alice_pubkey, 0, &bob_key),
+        // Alice's ballot doesn't carry Bob's vote, so Bob votes on his own
+        TxVote::new(&bob_pubkey, 0, &wonderland_pubkey, 0, &bob_key),
+    ]);
+    assert!(block.transactions[6].status().is_ok());
+    let snapshot = testkit.snapshot();
+    let schema = VotesSchema::new(&snapshot);
+    assert_eq!(schema.fan_wallet(&alice_pubkey).unwrap().weight(), 2);
+    assert_eq!(schema.ballot(0, &alice_pubkey).unwrap().weight(), 1);
+    assert_eq!(schema.ballot(0, &bob_pubkey).unwrap().weight(), 1);
+    assert_eq!(schema.tally(0, &wonderland_pubkey).unwrap().votes(), 2);
+    assert!(!schema.represented_fans(0).contains(&bob_pubkey));
+}
+
+
+#[test]
+fn test_delegate_after_delegate_ranked() {
+    let mut testkit = init_testkit();
+    let (alice_pubkey, alice_key) = crypto::gen_keypair();
+    let (bob_pubkey, bob_key) = crypto::gen_keypair();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    let (underland_pubkey, _) = crypto::gen_keypair();
+    let block = testkit.create_block_with_transactions(txvec![
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        TxCreateWallet::new(&bob_pubkey, "Bob", &bob_key),
+        register_team(&wonderland_pubkey, "Wonderland"),
+        register_team(&underland_pubkey, "Underland"),
+        create_ranked_election(vec![wonderland_pubkey, underland_pubkey]),
+        TxRankedVote::new(&alice_pubkey, 0, vec![wonderland_pubkey], 0, &alice_key),
+        TxDelegate::new(&bob_pubkey, &alice_pubkey, 0, &bob_key),
+        TxRankedVote::new(&bob_pubkey, 0, vec![underland_pubkey], 0, &bob_key),
+        // Changing the ranking doesn't pick up Bob's vote
+        TxRankedVote::new(&alice_pubkey, 0, vec![underland_pubkey], 1, &alice_key),
+    ]);
+    for tx in &block.transactions[7..9] {
+        assert!(tx.status().is_ok());
+    }
+    let snapshot = testkit.snapshot();
+    let schema = VotesSchema::new(&snapshot);
+    assert_eq!(schema.ranking(0, &alice_pubkey).unwrap().weight(), 1);
+    assert_eq!(schema.ranking(0, &bob_pubkey).unwrap().weight(), 1);
+    assert_eq!(schema.tally(0, &wonderland_pubkey).unwrap().votes(), 0);
+    assert_eq!(schema.tally(0, &underland_pubkey).unwrap().votes(), 2);
+}
+
+
+#[test]
+fn test_delegate_after_delegate_committed() {
+    let mut testkit = init_testkit();
+    let (alice_pubkey, alice_key) = crypto::gen_keypair();
+    let (bob_pubkey, bob_key) = crypto::gen_keypair();
+    let (wonderland_pubkey, _) = crypto::gen_keypair();
+    let (author_pubkey, author_key) = crypto::gen_keypair();
+    let salt = crypto::hash(b"salt");
+    let commitment = vote_commitment(&wonderland_pubkey, &salt);
+    let block = testkit.create_block_with_transactions(txvec![
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        TxCreateWallet::new(&bob_pubkey, "Bob", &bob_key),
+        register_team(&wonderland_pubkey, "Wonderland"),
+        TxCreateElection::new(&author_pubkey, "World Cup winner", vec![wonderland_pubkey],
+                              0, 10, 1, BallotMode::Single as u8, 3, 0, &author_key),
+        TxCommitVote::new(&alice_pubkey, 0, &commitment, 0, &alice_key),
+        TxDelegate::new(&bob_pubkey, &alice_pubkey, 0, &bob_key),
+        TxCommitVote::new(&bob_pubkey, 0, &commitment, 0, &bob_key),
+    ]);
+    assert!(block.transactions[6].status().is_ok());
+    testkit.create_block();
+    // Bob has committed his own vote, so Alice's reveal doesn't carry it
+    let block = testkit.create_block_with_transactions(txvec![
+        TxRevealVote::new(&alice_pubkey, 0, &wonderland_pubkey, &salt, 0, &alice_key),
+        TxRevealVote::new(&bob_pubkey, 0, &wonderland_pubkey, &salt, 0, &bob_key),
+    ]);
+    for tx in &block.transactions {
+        assert!(tx.status().is_ok());
+    }
+    let snapshot = testkit.snapshot();
+    let schema = VotesSchema::new(&snapshot);
+    assert_eq!(schema.ballot(0, &alice_pubkey).unwrap().weight(), 1);
+    assert_eq!(schema.ballot(0, &bob_pubkey).unwrap().weight(), 1);
+    assert_eq!(schema.tally(0, &wonderland_pubkey).unwrap().votes(), 2);
+}
+
+
+#[test]
+fn test_delegate_to_unknown_fan() {
+    let mut testkit = init_testkit();
+    let (alice_pubkey, alice_key) = crypto::gen_keypair();
+    let (bob_pubkey, _) = crypto::gen_keypair();
+    let block = testkit.create_block_with_transactions(txvec![
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        TxDelegate::new(&alice_pubkey, &bob_pubkey, 0, &alice_key),
+    ]);
+    let error = block.transactions[1].status().unwrap_err();
+    assert_eq!(error.error_type(), TransactionErrorType::Code(Error::DelegateNotFound as u8));
+    assert_eq!(error.description(), Some("Delegate doesn't exist"));
+    assert_eq!(Error::from_code(Error::DelegateNotFound as u8), Some(Error::DelegateNotFound));
+    let snapshot = testkit.snapshot();
+    assert!(!VotesSchema::new(&snapshot).fan_wallet(&alice_pubkey).unwrap().has_delegate());
+}
+
+
+#[test]
+fn test_delegation_cycle() {
+    let mut testkit = init_testkit();
+    let (alice_pubkey, alice_key) = crypto::gen_keypair();
+    let (bob_pubkey, bob_key) = crypto::gen_keypair();
+    let (carol_pubkey, carol_key) = crypto::gen_keypair();
+    let block = testkit.create_block_with_transactions(txvec![
+        TxCreateWallet::new(&alice_pubkey, "Alice", &alice_key),
+        TxCreateWallet::new(&bob_pubkey, "Bob", &bob_key),
+        TxCreateWallet::new(&carol_pubkey, "Carol", &carol_key),
+        TxDelegate::new(&alice_pubkey, &bob_pubkey, 0, &alice_key),
+        TxDelegate::new(&bob_pubkey, &carol_pubkey, 0, &bob_key),
+        TxDelegate::new(&carol_pubkey, &alice_pubkey, 0, &carol_key),
+        TxDelegate::new(&carol_pubkey, &carol_pubkey, 1, &carol_key),
+        TxUndelegate::new(&carol_pubkey, 0, &carol_key),
+    ]);
+    for tx in &block.transactions[5..7] {
+        assert_eq!(tx.status().unwrap_err().description(), Some("Delegation would create a cycle"));
     }
+    assert_eq!(
+        block.transactions[7].status().unwrap_err().description(),
+        Some("Fan hasn't delegated the vote")
+    );
+    let snapshot = testkit.snapshot();
+    let schema = VotesSchema::new(&snapshot);
+    assert!(!schema.fan_wallet(&carol_pubkey).unwrap().has_delegate());
+    assert_eq!(schema.fan_wallet(&carol_pubkey).unwrap().weight(), 3);
 }